@@ -0,0 +1,153 @@
+pub mod planner;
+pub mod report;
+pub mod topk;
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+#[derive(Debug)]
+pub enum InventoryError {
+    Io(io::Error),
+    InvalidLine { line: usize, content: String },
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InventoryError::Io(e) => write!(f, "I/O error: {}", e),
+            InventoryError::InvalidLine { line, content } => {
+                write!(
+                    f,
+                    "line {}: expected a calorie count, found {:?}",
+                    line, content
+                )
+            }
+        }
+    }
+}
+
+impl Error for InventoryError {}
+
+impl From<io::Error> for InventoryError {
+    fn from(e: io::Error) -> Self {
+        InventoryError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Elf {
+    pub index: usize,
+    pub items: Vec<i32>,
+}
+
+impl Elf {
+    pub fn total(&self) -> i32 {
+        self.items.iter().sum()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ElfInventory {
+    elves: Vec<Elf>,
+}
+
+impl ElfInventory {
+    pub fn from_path(path: &str) -> Result<Self, InventoryError> {
+        let reader = BufReader::new(File::open(path)?);
+        ElfInventory::from_reader(reader)
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, InventoryError> {
+        let mut elves = Vec::new();
+        scan_elves(reader, |items| {
+            elves.push(Elf {
+                index: elves.len(),
+                items: items.to_vec(),
+            })
+        })?;
+
+        Ok(ElfInventory { elves })
+    }
+
+    pub fn from_elves(elves: Vec<Elf>) -> Self {
+        ElfInventory { elves }
+    }
+
+    pub fn elves(&self) -> &[Elf] {
+        &self.elves
+    }
+
+    pub fn len(&self) -> usize {
+        self.elves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elves.is_empty()
+    }
+
+    pub fn totals(&self) -> Vec<i32> {
+        self.elves.iter().map(|e| e.total()).collect()
+    }
+
+    /// Sum of the `k` largest totals. If there are fewer than `k` elves, all
+    /// of them are counted.
+    pub fn sum_top_k(&self, k: usize) -> i32 {
+        let mut totals = self.totals();
+        totals.sort_unstable_by(|a, b| b.cmp(a));
+        totals.iter().take(k).sum()
+    }
+}
+
+/// Walk `reader` one elf at a time, handing each elf's items to `on_elf`.
+/// Returns the number of lines read.
+pub(crate) fn scan_elves<R, F>(mut reader: R, mut on_elf: F) -> Result<usize, InventoryError>
+where
+    R: BufRead,
+    F: FnMut(&[i32]),
+{
+    let mut line_buf = String::new();
+    let mut items = Vec::new();
+    let mut line_count = 0;
+
+    loop {
+        line_buf.clear();
+        if reader.read_line(&mut line_buf)? == 0 {
+            break;
+        }
+        line_count += 1;
+
+        let token = line_buf.trim();
+
+        // Blank line, move to the next elf
+        if token.is_empty() {
+            if !items.is_empty() {
+                on_elf(&items);
+                items.clear();
+            }
+            continue;
+        }
+
+        match token.parse::<i32>() {
+            Ok(calories) => items.push(calories),
+            Err(_) => {
+                return Err(InventoryError::InvalidLine {
+                    line: line_count,
+                    content: line_buf.trim_end_matches(['\n', '\r']).to_string(),
+                })
+            }
+        }
+    }
+
+    // The last elf is not followed by a blank line
+    if !items.is_empty() {
+        on_elf(&items);
+    }
+
+    Ok(line_count)
+}
+
+pub fn sumtopk_elves(path: &str, k: usize) -> Result<i32, InventoryError> {
+    Ok(ElfInventory::from_path(path)?.sum_top_k(k))
+}
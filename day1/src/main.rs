@@ -1,45 +1,54 @@
-pub mod day1 {
+use day1::report::CalorieReport;
+use day1::*;
 
-    use std::collections::BinaryHeap;
-    use std::fs;
-    use std::io::{BufRead, BufReader};
+use std::env;
+use std::process;
 
-    pub fn sumtopk_elves(path: &str, k: i32) -> i32 {
-        let reader = BufReader::new(fs::File::open(path).unwrap());
-
-        let mut most_cals = 0;
-        let mut curr_cals = 0;
-
-        let mut top_calories = BinaryHeap::new();
-
-        for line in reader.lines() {
-            match line.unwrap().parse::<i32>() {
-                Ok(calories) => {
-                    curr_cals += calories;
-                }
-
-                // Blank line, move to the next elf
-                Err(_e) => {
-                    if curr_cals > most_cals {
-                        most_cals = curr_cals;
-                    }
+fn usage() -> ! {
+    eprintln!("Usage: day1 [--format table|json] [--top K] [--bins N] [PATH]");
+    process::exit(2);
+}
 
-                    top_calories.push(curr_cals);
-                    curr_cals = 0;
-                }
+fn main() {
+    let mut path = String::from("day1/input.txt");
+    let mut json = false;
+    let mut k = 3;
+    let mut num_bins = 10;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref() {
+                Some("table") => json = false,
+                Some("json") => json = true,
+                _ => usage(),
+            },
+            "--top" => {
+                k = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| usage())
             }
+            "--bins" => {
+                num_bins = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| usage())
+            }
+            _ if arg.starts_with('-') => usage(),
+            _ => path = arg,
         }
-
-        let mut topk = 0;
-        for _ in 0..k {
-            topk += top_calories.pop().unwrap();
-        }
-        topk
     }
-}
 
-fn main() {
-    let fname: String = String::from("day1/input.txt");
-    let sumtop3 = day1::sumtopk_elves(&fname, 3);
-    println!("Top 3: {sumtop3}");
+    let inventory = ElfInventory::from_path(&path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    });
+
+    let report = CalorieReport::new(&inventory, k, num_bins);
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report);
+    }
 }
@@ -0,0 +1,289 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::{Elf, ElfInventory};
+
+/// Inventories with at most this many items are solved exactly by
+/// `plan_redistribution`; anything larger falls back to LPT.
+pub const EXACT_ITEM_LIMIT: usize = 20;
+
+#[derive(Debug, PartialEq)]
+pub enum PlanError {
+    NoElves,
+    CapTooSmall { items: usize, capacity: usize },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanError::NoElves => write!(f, "cannot plan for an empty inventory"),
+            PlanError::CapTooSmall { items, capacity } => write!(
+                f,
+                "{} items do not fit in a total capacity of {}",
+                items, capacity
+            ),
+        }
+    }
+}
+
+impl Error for PlanError {}
+
+/// Elves are identified by their position in `ElfInventory::elves`, not by
+/// `Elf::index`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemMove {
+    pub from: usize,
+    /// Position of the item in the original `Elf::items` list.
+    pub item: usize,
+    pub calories: i32,
+    pub to: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub moves: Vec<ItemMove>,
+    pub totals: Vec<i32>,
+}
+
+impl Plan {
+    pub fn max_total(&self) -> i32 {
+        self.totals.iter().copied().max().unwrap_or(0)
+    }
+
+    /// The inventory after every move has been carried out.
+    pub fn apply(&self, inventory: &ElfInventory) -> ElfInventory {
+        let mut kept: Vec<Vec<bool>> = inventory
+            .elves()
+            .iter()
+            .map(|e| vec![true; e.items.len()])
+            .collect();
+        for m in self.moves.iter() {
+            kept[m.from][m.item] = false;
+        }
+
+        let mut elves: Vec<Elf> = inventory
+            .elves()
+            .iter()
+            .zip(kept.iter())
+            .map(|(e, kept)| Elf {
+                index: e.index,
+                items: e
+                    .items
+                    .iter()
+                    .zip(kept.iter())
+                    .filter(|(_, k)| **k)
+                    .map(|(i, _)| *i)
+                    .collect(),
+            })
+            .collect();
+        for m in self.moves.iter() {
+            elves[m.to].items.push(m.calories);
+        }
+
+        ElfInventory::from_elves(elves)
+    }
+}
+
+struct Item {
+    /// Position of the elf in the inventory.
+    elf: usize,
+    idx: usize,
+    calories: i32,
+}
+
+fn flatten(inventory: &ElfInventory, cap: Option<usize>) -> Result<(Vec<Item>, usize), PlanError> {
+    let num_elves = inventory.len();
+    if num_elves == 0 {
+        return Err(PlanError::NoElves);
+    }
+
+    let mut items: Vec<Item> = inventory
+        .elves()
+        .iter()
+        .enumerate()
+        .flat_map(|(elf, e)| {
+            e.items
+                .iter()
+                .enumerate()
+                .map(move |(idx, &calories)| Item { elf, idx, calories })
+        })
+        .collect();
+
+    let cap = cap.unwrap_or(items.len());
+    if items.len() > cap.saturating_mul(num_elves) {
+        return Err(PlanError::CapTooSmall {
+            items: items.len(),
+            capacity: cap * num_elves,
+        });
+    }
+
+    // Placing big items first gives both solvers their best bounds
+    items.sort_by_key(|i| Reverse(i.calories));
+    Ok((items, cap))
+}
+
+/// Turn an assignment of items to interchangeable bins into moves between
+/// elves, matching each bin to the elf that already holds most of its items.
+fn build_plan(items: &[Item], bins: &[usize], num_elves: usize) -> Plan {
+    let mut overlap: HashMap<(usize, usize), usize> = HashMap::new();
+    for (item, &bin) in items.iter().zip(bins.iter()) {
+        *overlap.entry((bin, item.elf)).or_default() += 1;
+    }
+    let mut pairs: Vec<((usize, usize), usize)> = overlap.into_iter().collect();
+    pairs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut bin_to_elf: Vec<Option<usize>> = vec![None; num_elves];
+    let mut elf_taken = vec![false; num_elves];
+    for ((bin, elf), _) in pairs {
+        if bin_to_elf[bin].is_none() && !elf_taken[elf] {
+            bin_to_elf[bin] = Some(elf);
+            elf_taken[elf] = true;
+        }
+    }
+    let mut free_elves = (0..num_elves).filter(|&e| !elf_taken[e]);
+    let bin_to_elf: Vec<usize> = bin_to_elf
+        .into_iter()
+        .map(|e| e.unwrap_or_else(|| free_elves.next().unwrap()))
+        .collect();
+
+    let mut totals = vec![0; num_elves];
+    let mut moves = Vec::new();
+    for (item, &bin) in items.iter().zip(bins.iter()) {
+        let to = bin_to_elf[bin];
+        totals[to] += item.calories;
+        if to != item.elf {
+            moves.push(ItemMove {
+                from: item.elf,
+                item: item.idx,
+                calories: item.calories,
+                to,
+            });
+        }
+    }
+    moves.sort_by_key(|m| (m.from, m.item));
+
+    Plan { moves, totals }
+}
+
+fn lpt_bins(items: &[Item], num_elves: usize, cap: usize) -> Vec<usize> {
+    let mut loads = vec![0; num_elves];
+    let mut counts = vec![0; num_elves];
+
+    items
+        .iter()
+        .map(|item| {
+            let bin = (0..num_elves)
+                .filter(|&b| counts[b] < cap)
+                .min_by_key(|&b| loads[b])
+                .unwrap();
+            loads[bin] += item.calories;
+            counts[bin] += 1;
+            bin
+        })
+        .collect()
+}
+
+/// Longest-processing-time heuristic: hand out the largest remaining item to
+/// the lightest elf that still has room. Runs in O(items * elves).
+pub fn plan_lpt(inventory: &ElfInventory, cap: Option<usize>) -> Result<Plan, PlanError> {
+    let (items, cap) = flatten(inventory, cap)?;
+    let bins = lpt_bins(&items, inventory.len(), cap);
+    Ok(build_plan(&items, &bins, inventory.len()))
+}
+
+struct Search<'a> {
+    items: &'a [Item],
+    cap: usize,
+    lower_bound: i32,
+    loads: Vec<i32>,
+    counts: Vec<usize>,
+    bins: Vec<usize>,
+    best_max: i32,
+    best_bins: Vec<usize>,
+}
+
+impl Search<'_> {
+    fn run(&mut self, pos: usize, curr_max: i32) {
+        if self.best_max <= self.lower_bound {
+            return;
+        }
+        if pos == self.items.len() {
+            if curr_max < self.best_max {
+                self.best_max = curr_max;
+                self.best_bins = self.bins.clone();
+            }
+            return;
+        }
+
+        let calories = self.items[pos].calories;
+        let mut tried: Vec<(i32, usize)> = Vec::new();
+        for bin in 0..self.loads.len() {
+            let state = (self.loads[bin], self.counts[bin]);
+
+            // Bins in the same state are interchangeable
+            if state.1 >= self.cap || tried.contains(&state) {
+                continue;
+            }
+            tried.push(state);
+
+            let next_max = curr_max.max(state.0 + calories);
+            if next_max >= self.best_max {
+                continue;
+            }
+
+            self.loads[bin] += calories;
+            self.counts[bin] += 1;
+            self.bins[pos] = bin;
+            self.run(pos + 1, next_max);
+            self.loads[bin] -= calories;
+            self.counts[bin] -= 1;
+        }
+    }
+}
+
+/// Branch-and-bound search for the smallest possible maximum total. This is
+/// exponential in the number of items and only meant for small inventories.
+pub fn plan_exact(inventory: &ElfInventory, cap: Option<usize>) -> Result<Plan, PlanError> {
+    let (items, cap) = flatten(inventory, cap)?;
+    let num_elves = inventory.len();
+
+    let total: i32 = items.iter().map(|i| i.calories).sum();
+    let largest = items.first().map_or(0, |i| i.calories);
+    let lower_bound = largest.max((total + num_elves as i32 - 1) / num_elves as i32);
+
+    // Seed the search with the heuristic answer
+    let seed = lpt_bins(&items, num_elves, cap);
+    let mut seed_loads = vec![0; num_elves];
+    for (item, &bin) in items.iter().zip(seed.iter()) {
+        seed_loads[bin] += item.calories;
+    }
+
+    let mut search = Search {
+        items: &items,
+        cap,
+        lower_bound,
+        loads: vec![0; num_elves],
+        counts: vec![0; num_elves],
+        bins: vec![0; items.len()],
+        best_max: seed_loads.into_iter().max().unwrap_or(0),
+        best_bins: seed,
+    };
+    search.run(0, 0);
+
+    Ok(build_plan(&items, &search.best_bins, num_elves))
+}
+
+/// Exact for inventories of up to `EXACT_ITEM_LIMIT` items, LPT otherwise.
+pub fn plan_redistribution(
+    inventory: &ElfInventory,
+    cap: Option<usize>,
+) -> Result<Plan, PlanError> {
+    let num_items: usize = inventory.elves().iter().map(|e| e.items.len()).sum();
+    if num_items <= EXACT_ITEM_LIMIT {
+        plan_exact(inventory, cap)
+    } else {
+        plan_lpt(inventory, cap)
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use crate::ElfInventory;
+
+const PERCENTILES: [u32; 5] = [25, 50, 75, 90, 99];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramBin {
+    pub lo: i32,
+    pub hi: i32,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalorieReport {
+    pub num_elves: usize,
+    pub total: i64,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    pub percentiles: Vec<(u32, i32)>,
+    pub histogram: Vec<HistogramBin>,
+    /// `(elf_index, item_count)` of the elf carrying the most items.
+    pub most_items: Option<(usize, usize)>,
+    /// `(elf_index, total)` pairs, largest total first.
+    pub top_k: Vec<(usize, i32)>,
+}
+
+/// Nearest-rank percentile of already sorted values.
+pub fn percentile(sorted: &[i32], p: u32) -> Option<i32> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p.min(100) as usize * sorted.len()).div_ceil(100);
+    Some(sorted[rank.max(1) - 1])
+}
+
+pub fn median(sorted: &[i32]) -> Option<f64> {
+    let n = sorted.len();
+    if n == 0 {
+        return None;
+    }
+    if n % 2 == 1 {
+        Some(sorted[n / 2] as f64)
+    } else {
+        Some((sorted[n / 2 - 1] as f64 + sorted[n / 2] as f64) / 2.0)
+    }
+}
+
+/// Equal-width histogram spanning the smallest to the largest value.
+pub fn histogram(sorted: &[i32], num_bins: usize) -> Vec<HistogramBin> {
+    if sorted.is_empty() || num_bins == 0 {
+        return vec![];
+    }
+
+    let min = sorted[0] as i64;
+    let max = sorted[sorted.len() - 1] as i64;
+    let width = ((max - min + num_bins as i64) / num_bins as i64).max(1);
+
+    let mut bins: Vec<HistogramBin> = (0..num_bins as i64)
+        .map(|b| HistogramBin {
+            lo: (min + b * width) as i32,
+            hi: (min + (b + 1) * width - 1) as i32,
+            count: 0,
+        })
+        .take_while(|bin| bin.lo as i64 <= max)
+        .collect();
+
+    for &value in sorted {
+        let b = ((value as i64 - min) / width) as usize;
+        bins[b].count += 1;
+    }
+
+    bins
+}
+
+impl ElfInventory {
+    /// The `k` elves with the largest totals as `(elf_index, total)`. Ties
+    /// are broken by elf index.
+    pub fn top_k(&self, k: usize) -> Vec<(usize, i32)> {
+        let mut totals: Vec<(usize, i32)> =
+            self.elves().iter().map(|e| (e.index, e.total())).collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        totals.truncate(k);
+        totals
+    }
+
+    pub fn most_items(&self) -> Option<(usize, usize)> {
+        self.elves()
+            .iter()
+            .map(|e| (e.index, e.items.len()))
+            .reduce(|best, cand| if cand.1 > best.1 { cand } else { best })
+    }
+}
+
+impl CalorieReport {
+    pub fn new(inventory: &ElfInventory, k: usize, num_bins: usize) -> Self {
+        let mut sorted = inventory.totals();
+        sorted.sort_unstable();
+
+        let total: i64 = sorted.iter().map(|&t| t as i64).sum();
+        let mean = if sorted.is_empty() {
+            None
+        } else {
+            Some(total as f64 / sorted.len() as f64)
+        };
+
+        CalorieReport {
+            num_elves: sorted.len(),
+            total,
+            mean,
+            median: median(&sorted),
+            percentiles: PERCENTILES
+                .iter()
+                .filter_map(|&p| percentile(&sorted, p).map(|v| (p, v)))
+                .collect(),
+            histogram: histogram(&sorted, num_bins),
+            most_items: inventory.most_items(),
+            top_k: inventory.top_k(k),
+        }
+    }
+
+    pub fn top_k_sum(&self) -> i32 {
+        self.top_k.iter().map(|t| t.1).sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        fn opt_f64(v: Option<f64>) -> String {
+            v.map_or(String::from("null"), |v| format!("{:.2}", v))
+        }
+
+        let percentiles: Vec<String> = self
+            .percentiles
+            .iter()
+            .map(|(p, v)| format!("\"p{}\": {}", p, v))
+            .collect();
+        let histogram: Vec<String> = self
+            .histogram
+            .iter()
+            .map(|b| {
+                format!(
+                    "{{\"lo\": {}, \"hi\": {}, \"count\": {}}}",
+                    b.lo, b.hi, b.count
+                )
+            })
+            .collect();
+        let most_items = self
+            .most_items
+            .map_or(String::from("null"), |(elf, count)| {
+                format!("{{\"elf\": {}, \"items\": {}}}", elf, count)
+            });
+        let top_k: Vec<String> = self
+            .top_k
+            .iter()
+            .map(|(elf, total)| format!("{{\"elf\": {}, \"total\": {}}}", elf, total))
+            .collect();
+
+        format!(
+            "{{\"num_elves\": {}, \"total\": {}, \"mean\": {}, \"median\": {}, \
+             \"percentiles\": {{{}}}, \"histogram\": [{}], \"most_items\": {}, \
+             \"top_k\": [{}], \"top_k_sum\": {}}}",
+            self.num_elves,
+            self.total,
+            opt_f64(self.mean),
+            opt_f64(self.median),
+            percentiles.join(", "),
+            histogram.join(", "),
+            most_items,
+            top_k.join(", "),
+            self.top_k_sum(),
+        )
+    }
+}
+
+impl fmt::Display for CalorieReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn opt_f64(v: Option<f64>) -> String {
+            v.map_or(String::from("-"), |v| format!("{:.2}", v))
+        }
+
+        writeln!(f, "{:<12} {:>12}", "elves", self.num_elves)?;
+        writeln!(f, "{:<12} {:>12}", "total", self.total)?;
+        writeln!(f, "{:<12} {:>12}", "mean", opt_f64(self.mean))?;
+        writeln!(f, "{:<12} {:>12}", "median", opt_f64(self.median))?;
+        for (p, v) in self.percentiles.iter() {
+            writeln!(f, "{:<12} {:>12}", format!("p{}", p), v)?;
+        }
+        if let Some((elf, count)) = self.most_items {
+            writeln!(
+                f,
+                "{:<12} {:>12}",
+                "most items",
+                format!("#{} ({})", elf, count)
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{:>6} {:>12}", "elf", "calories")?;
+        for (elf, total) in self.top_k.iter() {
+            writeln!(f, "{:>6} {:>12}", elf, total)?;
+        }
+        writeln!(f, "Top {}: {}", self.top_k.len(), self.top_k_sum())?;
+
+        writeln!(f)?;
+        writeln!(f, "{:>13}  {:>6}", "range", "elves")?;
+        for bin in self.histogram.iter() {
+            writeln!(f, "{:>6}-{:<6}  {:>6}", bin.lo, bin.hi, bin.count)?;
+        }
+
+        Ok(())
+    }
+}
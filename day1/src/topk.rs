@@ -0,0 +1,159 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::thread;
+
+use crate::{scan_elves, InventoryError};
+
+const CHUNK_BYTES: usize = 1 << 20;
+
+/// Bounded collection of the `k` largest totals seen so far. Memory use is
+/// O(k) no matter how many totals are pushed.
+#[derive(Debug, Clone)]
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<i32>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> Self {
+        TopK {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub fn push(&mut self, total: i32) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(total));
+        } else if let Some(Reverse(smallest)) = self.heap.peek() {
+            if total > *smallest {
+                self.heap.pop();
+                self.heap.push(Reverse(total));
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: TopK) {
+        for Reverse(total) in other.heap {
+            self.push(total);
+        }
+    }
+
+    pub fn sum(&self) -> i32 {
+        self.heap.iter().map(|Reverse(t)| t).sum()
+    }
+
+    /// The retained totals, largest first.
+    pub fn into_sorted_vec(self) -> Vec<i32> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|r| r.0)
+            .collect()
+    }
+}
+
+pub fn topk_streaming<R: BufRead>(reader: R, k: usize) -> Result<TopK, InventoryError> {
+    let mut topk = TopK::new(k);
+    scan_elves(reader, |items| topk.push(items.iter().sum()))?;
+    Ok(topk)
+}
+
+/// Same answer as `sumtopk_elves`, but only ever holds `k` totals in memory.
+pub fn sumtopk_streaming(path: &str, k: usize) -> Result<i32, InventoryError> {
+    let reader = BufReader::with_capacity(CHUNK_BYTES, File::open(path)?);
+    Ok(topk_streaming(reader, k)?.sum())
+}
+
+/// Find the first byte offset at or after `pos` that begins a line directly
+/// following a blank line, or the end of the file if there is none.
+fn next_elf_boundary(file: &mut File, pos: u64, len: u64) -> Result<u64, InventoryError> {
+    if pos == 0 || pos >= len {
+        return Ok(pos.min(len));
+    }
+
+    file.seek(SeekFrom::Start(pos))?;
+    let mut reader = BufReader::with_capacity(CHUNK_BYTES, file);
+    let mut line_buf = String::new();
+
+    // Skip the (possibly partial) line we landed in
+    let mut offset = pos + reader.read_line(&mut line_buf)? as u64;
+    loop {
+        line_buf.clear();
+        let read = reader.read_line(&mut line_buf)?;
+        if read == 0 {
+            return Ok(len);
+        }
+        offset += read as u64;
+        if line_buf.trim().is_empty() {
+            return Ok(offset);
+        }
+    }
+}
+
+fn scan_chunk(path: &str, start: u64, end: u64, k: usize) -> Result<(usize, TopK), InventoryError> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let reader = BufReader::with_capacity(CHUNK_BYTES, file.take(end - start));
+
+    let mut topk = TopK::new(k);
+    let line_count = scan_elves(reader, |items| topk.push(items.iter().sum()))?;
+    Ok((line_count, topk))
+}
+
+/// Split the file into `num_chunks` pieces on blank-line boundaries, compute
+/// the top-k of each piece on its own thread and merge the results.
+pub fn sumtopk_parallel(path: &str, k: usize, num_chunks: usize) -> Result<i32, InventoryError> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let num_chunks = num_chunks.max(1) as u64;
+
+    let mut bounds = vec![0];
+    for idx in 1..num_chunks {
+        let boundary = next_elf_boundary(&mut file, len * idx / num_chunks, len)?;
+        if boundary > *bounds.last().unwrap() {
+            bounds.push(boundary);
+        }
+    }
+    if *bounds.last().unwrap() < len {
+        bounds.push(len);
+    }
+
+    let results: Vec<Result<(usize, TopK), InventoryError>> = thread::scope(|s| {
+        let handles: Vec<_> = bounds
+            .windows(2)
+            .map(|w| {
+                let (start, end) = (w[0], w[1]);
+                s.spawn(move || scan_chunk(path, start, end, k))
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("Chunk reader panicked."))
+            .collect()
+    });
+
+    // Report errors against their line number in the whole file
+    let mut merged = TopK::new(k);
+    let mut line_offset = 0;
+    for result in results {
+        match result {
+            Ok((line_count, topk)) => {
+                merged.merge(topk);
+                line_offset += line_count;
+            }
+            Err(InventoryError::InvalidLine { line, content }) => {
+                return Err(InventoryError::InvalidLine {
+                    line: line + line_offset,
+                    content,
+                })
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(merged.sum())
+}
@@ -0,0 +1,245 @@
+#[cfg(test)]
+mod tests_day1 {
+    use day1::*;
+
+    use day1::planner::*;
+    use day1::report::*;
+    use day1::topk::*;
+
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    fn generate_log(name: &str, num_elves: usize) -> PathBuf {
+        let mut state: u64 = 0x2022;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (state >> 33) as usize
+        };
+
+        let mut log = String::new();
+        for _ in 0..num_elves {
+            for _ in 0..(1 + next() % 6) {
+                log.push_str(&format!("{}\n", 1 + next() % 9999));
+            }
+            log.push('\n');
+        }
+
+        let path = std::env::temp_dir().join(format!("day1_{}_{}.txt", name, std::process::id()));
+        fs::write(&path, log).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_inventory_parse() {
+        let inventory = ElfInventory::from_path("input_test.txt").unwrap();
+        assert_eq!(inventory.len(), 5);
+        assert_eq!(inventory.elves()[0].items, vec![1000, 2000, 3000]);
+        assert_eq!(inventory.elves()[3].index, 3);
+        assert_eq!(inventory.totals(), vec![6000, 4000, 11000, 24000, 10000]);
+    }
+
+    #[test]
+    fn test_last_elf_without_blank_line() {
+        let inventory = ElfInventory::from_reader(Cursor::new("1\n2\n\n3")).unwrap();
+        assert_eq!(inventory.totals(), vec![3, 3]);
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        let err = ElfInventory::from_reader(Cursor::new("1\n\nabc\n")).unwrap_err();
+        match err {
+            InventoryError::InvalidLine { line, content } => {
+                assert_eq!(line, 3);
+                assert_eq!(content, "abc");
+            }
+            _ => panic!("Expected InvalidLine, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_top_k_larger_than_elves() {
+        let inventory = ElfInventory::from_path("input_test.txt").unwrap();
+        assert_eq!(inventory.sum_top_k(10), 55000);
+    }
+
+    #[test]
+    fn test_pt1() {
+        assert_eq!(sumtopk_elves("input_test.txt", 1).unwrap(), 24000);
+    }
+
+    #[test]
+    fn test_pt2() {
+        assert_eq!(sumtopk_elves("input_test.txt", 3).unwrap(), 45000);
+    }
+
+    #[test]
+    fn test_topk_bounded() {
+        let mut topk = TopK::new(2);
+        for total in [5, 1, 9, 3, 7] {
+            topk.push(total);
+        }
+        assert_eq!(topk.into_sorted_vec(), vec![9, 7]);
+    }
+
+    #[test]
+    fn test_streaming_matches() {
+        for k in [0, 1, 3, 10] {
+            assert_eq!(
+                sumtopk_streaming("input_test.txt", k).unwrap(),
+                sumtopk_elves("input_test.txt", k).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_parallel_matches() {
+        let path = generate_log("parallel", 2000);
+        let path = path.to_str().unwrap();
+
+        for k in [1, 3, 50] {
+            let expected = sumtopk_elves(path, k).unwrap();
+            assert_eq!(sumtopk_streaming(path, k).unwrap(), expected);
+            for num_chunks in [1, 2, 3, 7, 16] {
+                assert_eq!(sumtopk_parallel(path, k, num_chunks).unwrap(), expected);
+            }
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parallel_error_line() {
+        let path = std::env::temp_dir().join(format!("day1_badline_{}.txt", std::process::id()));
+        fs::write(&path, "1\n2\n\n3\n\n4\n\n5\nx\n").unwrap();
+
+        let err = sumtopk_parallel(path.to_str().unwrap(), 3, 4).unwrap_err();
+        match err {
+            InventoryError::InvalidLine { line, .. } => assert_eq!(line, 9),
+            _ => panic!("Expected InvalidLine, got {:?}", err),
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted = vec![15, 20, 35, 40, 50];
+        assert_eq!(percentile(&sorted, 5), Some(15));
+        assert_eq!(percentile(&sorted, 30), Some(20));
+        assert_eq!(percentile(&sorted, 50), Some(35));
+        assert_eq!(percentile(&sorted, 100), Some(50));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[test]
+    fn test_calorie_report() {
+        let inventory = ElfInventory::from_path("input_test.txt").unwrap();
+        let report = CalorieReport::new(&inventory, 3, 4);
+
+        assert_eq!(report.num_elves, 5);
+        assert_eq!(report.total, 55000);
+        assert_eq!(report.mean, Some(11000.0));
+        assert_eq!(report.median, Some(10000.0));
+        assert_eq!(report.most_items, Some((0, 3)));
+        assert_eq!(report.top_k, vec![(3, 24000), (2, 11000), (4, 10000)]);
+        assert_eq!(report.top_k_sum(), 45000);
+
+        let counts: Vec<usize> = report.histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 2, 0, 1]);
+        assert_eq!(report.histogram[0].lo, 4000);
+        assert!(report.histogram[3].hi >= 24000);
+    }
+
+    #[test]
+    fn test_calorie_report_json() {
+        let inventory = ElfInventory::from_reader(Cursor::new("1\n2\n\n4\n")).unwrap();
+        let json = CalorieReport::new(&inventory, 1, 1).to_json();
+        assert!(json.contains("\"top_k\": [{\"elf\": 1, \"total\": 4}]"));
+        assert!(json.contains("\"median\": 3.50"));
+
+        let empty = CalorieReport::new(&ElfInventory::default(), 3, 10);
+        assert!(empty.to_json().contains("\"mean\": null"));
+    }
+
+    #[test]
+    fn test_plan_exact() {
+        let inventory = ElfInventory::from_reader(Cursor::new("7\n5\n4\n\n3\n\n1\n")).unwrap();
+        let plan = plan_exact(&inventory, None).unwrap();
+
+        // 20 calories over three elves can't beat 7
+        assert_eq!(plan.max_total(), 7);
+        assert_eq!(plan.moves.len(), 2);
+
+        let after = plan.apply(&inventory);
+        assert_eq!(after.totals(), plan.totals);
+        assert_eq!(after.totals().iter().sum::<i32>(), 20);
+    }
+
+    #[test]
+    fn test_plan_cap() {
+        let inventory = ElfInventory::from_reader(Cursor::new("1\n1\n1\n1\n\n9\n")).unwrap();
+        let plan = plan_exact(&inventory, Some(3)).unwrap();
+        assert_eq!(plan.max_total(), 10);
+        assert!(plan
+            .apply(&inventory)
+            .elves()
+            .iter()
+            .all(|e| e.items.len() <= 3));
+
+        assert_eq!(
+            plan_exact(&inventory, Some(2)),
+            Err(PlanError::CapTooSmall {
+                items: 5,
+                capacity: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_plan_sparse_indices() {
+        let inventory = ElfInventory::from_elves(vec![
+            Elf {
+                index: 4,
+                items: vec![7, 5, 4],
+            },
+            Elf {
+                index: 9,
+                items: vec![3],
+            },
+        ]);
+        let plan = plan_exact(&inventory, None).unwrap();
+        assert_eq!(plan.max_total(), 10);
+
+        let after = plan.apply(&inventory);
+        assert_eq!(after.totals(), plan.totals);
+        assert_eq!(
+            after.elves().iter().map(|e| e.index).collect::<Vec<_>>(),
+            vec![4, 9]
+        );
+    }
+
+    #[test]
+    fn test_plan_lpt_vs_exact() {
+        let inventory = ElfInventory::from_path("input_test.txt").unwrap();
+        let exact = plan_exact(&inventory, None).unwrap();
+        let lpt = plan_lpt(&inventory, None).unwrap();
+
+        assert_eq!(exact.max_total(), 11000);
+        assert!(lpt.max_total() >= exact.max_total());
+        assert_eq!(plan_redistribution(&inventory, None).unwrap(), exact);
+    }
+
+    #[test]
+    fn test_plan_lpt_large() {
+        let path = generate_log("lpt", 500);
+        let inventory = ElfInventory::from_path(path.to_str().unwrap()).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let plan = plan_redistribution(&inventory, Some(4)).unwrap();
+        let after = plan.apply(&inventory);
+        assert_eq!(after.totals(), plan.totals);
+        assert!(after.elves().iter().all(|e| e.items.len() <= 4));
+        assert!(plan.max_total() < inventory.totals().into_iter().max().unwrap());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::rules::{RuleSet, RuleSetError, Weapon};
+use crate::{compute_desired_hand, Outcome, RockPaperScissors};
+
+/// Decides which weapon we play in a round, given the opponent's weapon and
+/// the symbol in our column. The round stores our symbol as the weapon it
+/// names under the rule set, which decoders are free to ignore.
+pub trait ColumnDecoder {
+    fn decode(&self, rules: &RuleSet, round: &RockPaperScissors) -> Weapon;
+}
+
+/// Part 1: our symbol is the shape to play.
+pub struct ShapeDecoder;
+
+impl ColumnDecoder for ShapeDecoder {
+    fn decode(&self, _rules: &RuleSet, round: &RockPaperScissors) -> Weapon {
+        round.p2()
+    }
+}
+
+/// Part 2: our symbol is the outcome to aim for.
+pub struct OutcomeDecoder;
+
+impl ColumnDecoder for OutcomeDecoder {
+    fn decode(&self, rules: &RuleSet, round: &RockPaperScissors) -> Weapon {
+        compute_desired_hand(rules, round)
+    }
+}
+
+/// Our symbol counts steps ahead of the opponent's shape: the i-th symbol
+/// plays the weapon `i + steps` places after theirs in rule set order.
+pub struct ShiftDecoder {
+    pub steps: usize,
+}
+
+impl ColumnDecoder for ShiftDecoder {
+    fn decode(&self, rules: &RuleSet, round: &RockPaperScissors) -> Weapon {
+        Weapon((round.p1().0 + round.p2().0 + self.steps) % rules.len())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reading {
+    Shape(Weapon),
+    Outcome(Outcome),
+}
+
+/// A user-provided meaning for each of our symbols.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDecoder {
+    readings: Vec<Reading>,
+}
+
+impl TableDecoder {
+    /// Build a table from `(symbol, reading)` pairs. Every one of our symbols
+    /// in `rules` needs exactly one entry.
+    pub fn new(rules: &RuleSet, entries: &[(&str, Reading)]) -> Result<Self, RuleSetError> {
+        let mut readings: Vec<Option<Reading>> = vec![None; rules.len()];
+        for (symbol, reading) in entries {
+            let weapon = rules
+                .parse_player(symbol)
+                .ok_or_else(|| RuleSetError::new(0, &format!("unknown symbol {}", symbol)))?;
+            if readings[weapon.0].replace(*reading).is_some() {
+                return Err(RuleSetError::new(
+                    0,
+                    &format!("symbol {} given twice", symbol),
+                ));
+            }
+        }
+
+        let readings = readings
+            .into_iter()
+            .enumerate()
+            .map(|(idx, r)| {
+                r.ok_or_else(|| {
+                    RuleSetError::new(
+                        0,
+                        &format!("no reading for symbol {}", rules.player_symbol(Weapon(idx))),
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(TableDecoder { readings })
+    }
+
+    /// Parse lines of `<symbol> <weapon name | lose | draw | win>`.
+    pub fn parse(rules: &RuleSet, spec: &str) -> Result<Self, RuleSetError> {
+        let mut entries = vec![];
+        for (idx, line) in spec.lines().enumerate() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let err = |msg: &str| RuleSetError::new(idx + 1, msg);
+
+            match tokens.as_slice() {
+                [] => continue,
+                [symbol, meaning] => {
+                    let reading = match *meaning {
+                        "lose" => Reading::Outcome(Outcome::Lose),
+                        "draw" => Reading::Outcome(Outcome::Draw),
+                        "win" => Reading::Outcome(Outcome::Win),
+                        name => Reading::Shape(
+                            rules
+                                .weapon_by_name(name)
+                                .ok_or_else(|| err(&format!("unknown weapon {}", name)))?,
+                        ),
+                    };
+                    entries.push((*symbol, reading));
+                }
+                _ => return Err(err(&format!("could not parse {:?}", line.trim()))),
+            }
+        }
+
+        TableDecoder::new(rules, &entries)
+    }
+
+    pub(crate) fn from_readings(readings: Vec<Reading>) -> Self {
+        TableDecoder { readings }
+    }
+
+    pub fn readings(&self) -> &[Reading] {
+        &self.readings
+    }
+}
+
+impl ColumnDecoder for TableDecoder {
+    fn decode(&self, rules: &RuleSet, round: &RockPaperScissors) -> Weapon {
+        match self.readings[round.p2().0] {
+            Reading::Shape(weapon) => weapon,
+            Reading::Outcome(outcome) => rules.desired(round.p1(), outcome),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_is_outcome_for_classic() {
+        // Same shape draws, one ahead wins, two ahead loses
+        let rules = RuleSet::classic();
+        let decoder = ShiftDecoder { steps: 0 };
+        for p1 in rules.weapons() {
+            for p2 in rules.weapons() {
+                let round = RockPaperScissors::new(p1, p2);
+                let expected = [Outcome::Draw, Outcome::Win, Outcome::Lose][p2.0];
+                assert_eq!(rules.outcome(decoder.decode(&rules, &round), p1), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_parse() {
+        let rules = RuleSet::classic();
+        let table = TableDecoder::parse(&rules, "X Paper\nY win\nZ Rock\n").unwrap();
+        assert_eq!(
+            table.readings(),
+            &[
+                Reading::Shape(Weapon(1)),
+                Reading::Outcome(Outcome::Win),
+                Reading::Shape(Weapon(0))
+            ]
+        );
+
+        let round = RockPaperScissors::new(Weapon(2), Weapon(1));
+        assert_eq!(table.decode(&rules, &round), Weapon(0));
+
+        assert!(TableDecoder::parse(&rules, "X Paper\nY win\n").is_err());
+        assert!(TableDecoder::parse(&rules, "X Paper\nX win\nZ Rock\n").is_err());
+        assert_eq!(
+            TableDecoder::parse(&rules, "X Paper\nY Lizard\n")
+                .unwrap_err()
+                .line,
+            2
+        );
+    }
+}
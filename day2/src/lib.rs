@@ -1,20 +1,33 @@
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
 
-#[derive(Debug, Clone, Copy)]
+pub mod decoder;
+pub mod report;
+pub mod rules;
+pub mod solver;
+pub mod strategy;
+pub mod tournament;
+
+use decoder::{ColumnDecoder, OutcomeDecoder, ShapeDecoder};
+use rules::{RuleSet, Weapon, CLASSIC};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum State {
     Rock,
     Paper,
     Scissors,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Outcome {
     Lose,
     Draw,
     Win,
 }
 
-fn match_value(result: Outcome) -> i32 {
+pub(crate) fn match_value(result: Outcome) -> i32 {
     match result {
         Outcome::Lose => 0,
         Outcome::Draw => 3,
@@ -23,126 +36,266 @@ fn match_value(result: Outcome) -> i32 {
 }
 
 impl State {
-    pub fn parse(symbol: &str) -> State {
+    pub fn parse(symbol: &str) -> Option<State> {
         match symbol {
-            "A" => State::Rock,
-            "B" => State::Paper,
-            "C" => State::Scissors,
-            "X" => State::Rock,
-            "Y" => State::Paper,
-            "Z" => State::Scissors,
-            &_ => todo!(),
+            "A" => Some(State::Rock),
+            "B" => Some(State::Paper),
+            "C" => Some(State::Scissors),
+            "X" => Some(State::Rock),
+            "Y" => Some(State::Paper),
+            "Z" => Some(State::Scissors),
+            &_ => None,
         }
     }
 
-    pub fn value(&self) -> i32 {
-        match self {
-            State::Rock => 1,
-            State::Paper => 2,
-            State::Scissors => 3,
+    pub fn from_weapon(weapon: Weapon) -> Option<State> {
+        match weapon {
+            Weapon(0) => Some(State::Rock),
+            Weapon(1) => Some(State::Paper),
+            Weapon(2) => Some(State::Scissors),
+            _ => None,
         }
     }
 
+    /// This shape under the classic rule set.
+    pub fn weapon(&self) -> Weapon {
+        Weapon(*self as usize)
+    }
+
+    pub fn value(&self) -> i32 {
+        CLASSIC.value(self.weapon())
+    }
+
     pub fn score_versus(&self, other: State) -> Outcome {
-        match self {
-            State::Rock => match other {
-                State::Rock => Outcome::Draw,
-                State::Paper => Outcome::Lose,
-                State::Scissors => Outcome::Win,
-            },
-            State::Paper => match other {
-                State::Rock => Outcome::Win,
-                State::Paper => Outcome::Draw,
-                State::Scissors => Outcome::Lose,
-            },
-            State::Scissors => match other {
-                State::Rock => Outcome::Lose,
-                State::Paper => Outcome::Win,
-                State::Scissors => Outcome::Draw,
-            },
-        }
+        CLASSIC.outcome(self.weapon(), other.weapon())
     }
 }
 
 #[derive(Debug)]
 pub struct RockPaperScissors {
-    p1: State,
-    p2: State,
+    p1: Weapon,
+    p2: Weapon,
 }
 
 impl RockPaperScissors {
-    fn parse(player1: &str, player2: &str) -> RockPaperScissors {
-        RockPaperScissors {
-            p1: State::parse(player1),
-            p2: State::parse(player2),
+    pub fn new(p1: Weapon, p2: Weapon) -> Self {
+        RockPaperScissors { p1, p2 }
+    }
+
+    pub fn p1(&self) -> Weapon {
+        self.p1
+    }
+
+    pub fn p2(&self) -> Weapon {
+        self.p2
+    }
+
+    pub fn score_p2(&self, rules: &RuleSet) -> i32 {
+        rules.value(self.p2) + match_value(rules.outcome(self.p2, self.p1))
+    }
+}
+
+#[derive(Debug)]
+pub enum GuideErrorKind {
+    /// The guide file couldn't be opened; `token` holds its path.
+    Open(io::Error),
+    Io(io::Error),
+    UnknownSymbol,
+    MissingSymbol,
+    ExtraSymbol,
+}
+
+#[derive(Debug)]
+pub struct GuideParseError {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub kind: GuideErrorKind,
+}
+
+impl fmt::Display for GuideParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let GuideErrorKind::Open(e) = &self.kind {
+            return write!(f, "could not open {}: {}", self.token, e);
+        }
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            GuideErrorKind::Io(e) => write!(f, "could not read line: {}", e),
+            GuideErrorKind::UnknownSymbol => write!(f, "unknown symbol {:?}", self.token),
+            GuideErrorKind::MissingSymbol => write!(f, "expected two symbols"),
+            GuideErrorKind::ExtraSymbol => write!(f, "unexpected symbol {:?}", self.token),
+            GuideErrorKind::Open(_) => Ok(()),
+        }
+    }
+}
+
+impl Error for GuideParseError {}
+
+impl GuideParseError {
+    fn new(line: usize, column: usize, token: &str, kind: GuideErrorKind) -> Self {
+        GuideParseError {
+            line,
+            column,
+            token: String::from(token),
+            kind,
         }
     }
+}
+
+/// Split a line into its tokens, paired with their 1-based column.
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    for (col, (byte, ch)) in line.char_indices().enumerate() {
+        match (ch.is_whitespace(), start) {
+            (false, None) => start = Some((byte, col + 1)),
+            (true, Some((s, column))) => {
+                tokens.push((column, &line[s..byte]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((s, column)) = start {
+        tokens.push((column, &line[s..]));
+    }
+    tokens
+}
 
-    fn score_p2(&self) -> i32 {
-        self.p2.value() + match_value(self.p2.score_versus(self.p1))
+fn parse_round(
+    rules: &RuleSet,
+    line_num: usize,
+    line: &str,
+) -> Result<RockPaperScissors, GuideParseError> {
+    let tokens = tokenize(line);
+    if tokens.len() < 2 {
+        let column = line.chars().count() + 1;
+        return Err(GuideParseError::new(
+            line_num,
+            column,
+            "",
+            GuideErrorKind::MissingSymbol,
+        ));
+    }
+    if tokens.len() > 2 {
+        let (column, token) = tokens[2];
+        return Err(GuideParseError::new(
+            line_num,
+            column,
+            token,
+            GuideErrorKind::ExtraSymbol,
+        ));
     }
+
+    let symbol = |(column, token): (usize, &str), weapon: Option<Weapon>| {
+        weapon.ok_or_else(|| {
+            GuideParseError::new(line_num, column, token, GuideErrorKind::UnknownSymbol)
+        })
+    };
+
+    Ok(RockPaperScissors {
+        p1: symbol(tokens[0], rules.parse_opponent(tokens[0].1))?,
+        p2: symbol(tokens[1], rules.parse_player(tokens[1].1))?,
+    })
 }
 
-pub fn parse_strategy_guide(path: &str) -> Vec<RockPaperScissors> {
+fn parse_guide_lines<R, F>(rules: &RuleSet, reader: R, mut on_error: F) -> Vec<RockPaperScissors>
+where
+    R: BufRead,
+    F: FnMut(GuideParseError) -> bool,
+{
     let mut guide: Vec<RockPaperScissors> = vec![];
-    let reader = BufReader::new(File::open(path).unwrap());
-    for line in reader.lines() {
-        let line = line.unwrap();
+    for (idx, line) in reader.lines().enumerate() {
+        let parsed = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => parse_round(rules, idx + 1, &line),
+            Err(e) => Err(GuideParseError::new(idx + 1, 1, "", GuideErrorKind::Io(e))),
+        };
 
-        let mut hand: Vec<String> = vec![];
-        for inp in line.split_whitespace() {
-            hand.push(String::from(inp));
+        match parsed {
+            Ok(round) => guide.push(round),
+            Err(e) => {
+                if !on_error(e) {
+                    break;
+                }
+            }
         }
-        assert_eq!(hand.len(), 2);
+    }
+    guide
+}
+
+/// Parse a guide, stopping at the first malformed round. Blank lines are
+/// ignored.
+pub fn parse_strategy_guide<R: BufRead>(
+    reader: R,
+) -> Result<Vec<RockPaperScissors>, GuideParseError> {
+    parse_strategy_guide_with(&CLASSIC, reader)
+}
 
-        guide.push(RockPaperScissors::parse(&hand[0], &hand[1]));
+pub fn parse_strategy_guide_with<R: BufRead>(
+    rules: &RuleSet,
+    reader: R,
+) -> Result<Vec<RockPaperScissors>, GuideParseError> {
+    let mut first_err = None;
+    let guide = parse_guide_lines(rules, reader, |e| {
+        first_err = Some(e);
+        false
+    });
+
+    match first_err {
+        Some(e) => Err(e),
+        None => Ok(guide),
     }
+}
 
-    guide
+/// Parse a guide, skipping malformed rounds and collecting their errors.
+pub fn parse_strategy_guide_lenient<R: BufRead>(
+    reader: R,
+) -> (Vec<RockPaperScissors>, Vec<GuideParseError>) {
+    parse_strategy_guide_lenient_with(&CLASSIC, reader)
 }
 
-fn compute_desired_hand(hand: &RockPaperScissors) -> State {
-    // Part 2: convert X, Y, Z => L, D, W
-    let desired: Outcome = match hand.p2 {
-        State::Rock => Outcome::Lose,
-        State::Paper => Outcome::Draw,
-        State::Scissors => Outcome::Win,
-    };
+pub fn parse_strategy_guide_lenient_with<R: BufRead>(
+    rules: &RuleSet,
+    reader: R,
+) -> (Vec<RockPaperScissors>, Vec<GuideParseError>) {
+    let mut errors = vec![];
+    let guide = parse_guide_lines(rules, reader, |e| {
+        let is_io = matches!(e.kind, GuideErrorKind::Io(_));
+        errors.push(e);
+        !is_io
+    });
+    (guide, errors)
+}
+
+pub fn parse_strategy_file(path: &str) -> Result<Vec<RockPaperScissors>, GuideParseError> {
+    let file =
+        File::open(path).map_err(|e| GuideParseError::new(0, 0, path, GuideErrorKind::Open(e)))?;
+    parse_strategy_guide(BufReader::new(file))
+}
 
-    match hand.p1 {
-        State::Rock => match desired {
-            Outcome::Lose => State::Scissors,
-            Outcome::Draw => State::Rock,
-            Outcome::Win => State::Paper,
-        },
-        State::Paper => match desired {
-            Outcome::Lose => State::Rock,
-            Outcome::Draw => State::Paper,
-            Outcome::Win => State::Scissors,
-        },
-        State::Scissors => match desired {
-            Outcome::Lose => State::Paper,
-            Outcome::Draw => State::Scissors,
-            Outcome::Win => State::Rock,
-        },
-    }
-}
-
-fn compute_ideal(guide: &[RockPaperScissors]) -> Vec<RockPaperScissors> {
+/// Part 2: read our symbol as the outcome to aim for, and pick the weapon
+/// that gets it.
+pub fn compute_desired_hand(rules: &RuleSet, hand: &RockPaperScissors) -> Weapon {
+    rules.desired(hand.p1, rules.symbol_outcome(hand.p2))
+}
+
+/// Score every round after `decoder` has decided what our column means.
+pub fn score_strategy_guide_with(
+    rules: &RuleSet,
+    guide: &[RockPaperScissors],
+    decoder: &dyn ColumnDecoder,
+) -> i32 {
     guide
         .iter()
-        .map(|g| RockPaperScissors {
-            p1: g.p1,
-            p2: compute_desired_hand(g),
-        })
-        .collect()
+        .map(|g| RockPaperScissors::new(g.p1, decoder.decode(rules, g)).score_p2(rules))
+        .sum()
 }
 
 pub fn score_strategy_guide(guide: &[RockPaperScissors]) -> i32 {
-    guide.iter().map(|g| g.score_p2()).sum()
+    score_strategy_guide_with(&CLASSIC, guide, &ShapeDecoder)
 }
 
 pub fn score_strategy_guide_pt2(guide: &[RockPaperScissors]) -> i32 {
-    let ideal_guide = compute_ideal(guide);
-    score_strategy_guide(&ideal_guide)
+    score_strategy_guide_with(&CLASSIC, guide, &OutcomeDecoder)
 }
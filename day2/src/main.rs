@@ -1,7 +1,120 @@
+use day2::decoder::{OutcomeDecoder, ShapeDecoder};
+use day2::report::GuideReport;
+use day2::rules::RuleSet;
+use day2::strategy::*;
+use day2::tournament::Tournament;
 use day2::*;
 
+use std::env;
+use std::process;
+
+fn usage() -> ! {
+    eprintln!("Usage: day2 [PATH]");
+    eprintln!("       day2 report [--csv] [PATH]");
+    eprintln!("       day2 tournament [--rounds N] [--swiss ROUNDS] [--seed S] [--guide PATH]");
+    process::exit(2);
+}
+
+fn parse_guide(path: &str) -> Vec<RockPaperScissors> {
+    parse_strategy_file(path).unwrap_or_else(|e| {
+        match e.kind {
+            // Already names the path
+            GuideErrorKind::Open(_) => eprintln!("{}", e),
+            _ => eprintln!("{}: {}", path, e),
+        }
+        process::exit(1);
+    })
+}
+
+fn player<S: Strategy + 'static>(strategy: Result<S, StrategyError>) -> Box<dyn Strategy> {
+    Box::new(strategy.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    }))
+}
+
+fn tournament(mut args: impl Iterator<Item = String>) {
+    let mut rounds_per_match = 100;
+    let mut swiss_rounds: Option<usize> = None;
+    let mut seed = 2022;
+    let mut guide_path = String::from("day2/input.txt");
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--rounds" => rounds_per_match = value().parse().unwrap_or_else(|_| usage()),
+            "--swiss" => swiss_rounds = Some(value().parse().unwrap_or_else(|_| usage())),
+            "--seed" => seed = value().parse().unwrap_or_else(|_| usage()),
+            "--guide" => guide_path = value(),
+            _ => usage(),
+        }
+    }
+
+    let guide = parse_guide(&guide_path);
+    let rules = RuleSet::classic();
+
+    let mut t = Tournament::new(rounds_per_match);
+    t.add(Box::new(ConstantStrategy(State::Rock)));
+    t.add(Box::new(ConstantStrategy(State::Paper)));
+    t.add(Box::new(ConstantStrategy(State::Scissors)));
+    t.add(player(CyclicStrategy::new(vec![
+        State::Rock,
+        State::Paper,
+        State::Scissors,
+    ])));
+    t.add(Box::new(RandomStrategy::new(seed)));
+    t.add(player(FrequencyStrategy::new(&rules)));
+    if !guide.is_empty() {
+        t.add(player(ReplayStrategy::from_guide(
+            "guide-shapes",
+            &rules,
+            &guide,
+            &ShapeDecoder,
+        )));
+        t.add(player(ReplayStrategy::from_guide(
+            "guide-outcomes",
+            &rules,
+            &guide,
+            &OutcomeDecoder,
+        )));
+    }
+
+    let leaderboard = match swiss_rounds {
+        Some(rounds) => t.swiss(rounds, seed),
+        None => t.round_robin(),
+    };
+    print!("{}", leaderboard);
+}
+
+fn report(args: impl Iterator<Item = String>) {
+    let mut csv = false;
+    let mut path = String::from("day2/input.txt");
+    for arg in args {
+        match arg.as_str() {
+            "--csv" => csv = true,
+            _ if arg.starts_with('-') => usage(),
+            _ => path = arg,
+        }
+    }
+
+    let report = GuideReport::classic(&RuleSet::classic(), &parse_guide(&path));
+    if csv {
+        print!("{}", report.to_csv());
+    } else {
+        print!("{}", report.to_table());
+    }
+}
+
 fn main() {
-    let guide = parse_strategy_guide("day2/input.txt");
-    println!("Part-1: {:?}", score_strategy_guide(&guide));
-    println!("Part-2: {:?}", score_strategy_guide_pt2(&guide));
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("tournament") => tournament(args),
+        Some("report") => report(args),
+        Some(arg) if arg.starts_with('-') => usage(),
+        path => {
+            let guide = parse_guide(path.unwrap_or("day2/input.txt"));
+            println!("Part-1: {:?}", score_strategy_guide(&guide));
+            println!("Part-2: {:?}", score_strategy_guide_pt2(&guide));
+        }
+    }
 }
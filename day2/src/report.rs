@@ -0,0 +1,209 @@
+use std::fmt::Write;
+
+use crate::decoder::{ColumnDecoder, OutcomeDecoder, ShapeDecoder};
+use crate::rules::{RuleSet, Weapon};
+use crate::{match_value, Outcome, RockPaperScissors};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundScore {
+    pub mine: Weapon,
+    pub outcome: Outcome,
+    pub shape_points: i32,
+    pub outcome_points: i32,
+}
+
+impl RoundScore {
+    pub fn new(rules: &RuleSet, opponent: Weapon, mine: Weapon) -> Self {
+        let outcome = rules.outcome(mine, opponent);
+        RoundScore {
+            mine,
+            outcome,
+            shape_points: rules.value(mine),
+            outcome_points: match_value(outcome),
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.shape_points + self.outcome_points
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tally {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub shape_points: i32,
+    pub outcome_points: i32,
+}
+
+impl Tally {
+    pub fn total(&self) -> i32 {
+        self.shape_points + self.outcome_points
+    }
+
+    fn add(&mut self, score: &RoundScore) {
+        match score.outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Lose => self.losses += 1,
+        }
+        self.shape_points += score.shape_points;
+        self.outcome_points += score.outcome_points;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundReport {
+    pub opponent: Weapon,
+    /// Our column, as the weapon its symbol names.
+    pub symbol: Weapon,
+    pub part1: RoundScore,
+    pub part2: RoundScore,
+}
+
+impl RoundReport {
+    pub fn outcome_changed(&self) -> bool {
+        self.part1.outcome != self.part2.outcome
+    }
+}
+
+/// Every round of a guide scored under two readings of our column, by
+/// default the puzzle's part 1 (shapes) and part 2 (outcomes).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuideReport {
+    rules: RuleSet,
+    pub rounds: Vec<RoundReport>,
+}
+
+impl GuideReport {
+    pub fn new(
+        rules: &RuleSet,
+        guide: &[RockPaperScissors],
+        part1: &dyn ColumnDecoder,
+        part2: &dyn ColumnDecoder,
+    ) -> Self {
+        let rounds = guide
+            .iter()
+            .map(|round| RoundReport {
+                opponent: round.p1(),
+                symbol: round.p2(),
+                part1: RoundScore::new(rules, round.p1(), part1.decode(rules, round)),
+                part2: RoundScore::new(rules, round.p1(), part2.decode(rules, round)),
+            })
+            .collect();
+
+        GuideReport {
+            rules: rules.clone(),
+            rounds,
+        }
+    }
+
+    pub fn classic(rules: &RuleSet, guide: &[RockPaperScissors]) -> Self {
+        GuideReport::new(rules, guide, &ShapeDecoder, &OutcomeDecoder)
+    }
+
+    pub fn tally_pt1(&self) -> Tally {
+        let mut tally = Tally::default();
+        self.rounds.iter().for_each(|r| tally.add(&r.part1));
+        tally
+    }
+
+    pub fn tally_pt2(&self) -> Tally {
+        let mut tally = Tally::default();
+        self.rounds.iter().for_each(|r| tally.add(&r.part2));
+        tally
+    }
+
+    /// Indices of the rounds whose outcome differs between the readings.
+    pub fn changed_rounds(&self) -> Vec<usize> {
+        (0..self.rounds.len())
+            .filter(|&idx| self.rounds[idx].outcome_changed())
+            .collect()
+    }
+
+    fn outcome_name(outcome: Outcome) -> &'static str {
+        match outcome {
+            Outcome::Lose => "lose",
+            Outcome::Draw => "draw",
+            Outcome::Win => "win",
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "round,opponent,symbol,\
+             pt1_shape,pt1_outcome,pt1_shape_points,pt1_outcome_points,\
+             pt2_shape,pt2_outcome,pt2_shape_points,pt2_outcome_points,changed\n",
+        );
+        for (idx, r) in self.rounds.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{},{},{}",
+                idx + 1,
+                self.rules.opponent_symbol(r.opponent),
+                self.rules.player_symbol(r.symbol),
+                self.rules.name(r.part1.mine),
+                GuideReport::outcome_name(r.part1.outcome),
+                r.part1.shape_points,
+                r.part1.outcome_points,
+                self.rules.name(r.part2.mine),
+                GuideReport::outcome_name(r.part2.outcome),
+                r.part2.shape_points,
+                r.part2.outcome_points,
+                r.outcome_changed()
+            );
+        }
+        out
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>6}  {:<4}  {:<10} {:<5} {:>5}  {:<10} {:<5} {:>5}",
+            "round", "hand", "part 1", "", "pts", "part 2", "", "pts"
+        );
+        for (idx, r) in self.rounds.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{:>6}  {:<4}  {:<10} {:<5} {:>5}  {:<10} {:<5} {:>5}{}",
+                idx + 1,
+                format!(
+                    "{} {}",
+                    self.rules.opponent_symbol(r.opponent),
+                    self.rules.player_symbol(r.symbol)
+                ),
+                self.rules.name(r.part1.mine),
+                GuideReport::outcome_name(r.part1.outcome),
+                format!("{}+{}", r.part1.shape_points, r.part1.outcome_points),
+                self.rules.name(r.part2.mine),
+                GuideReport::outcome_name(r.part2.outcome),
+                format!("{}+{}", r.part2.shape_points, r.part2.outcome_points),
+                if r.outcome_changed() { "  *" } else { "" }
+            );
+        }
+
+        let _ = writeln!(out);
+        for (name, tally) in [("part 1", self.tally_pt1()), ("part 2", self.tally_pt2())] {
+            let _ = writeln!(
+                out,
+                "{}: {} ({} shape + {} outcome), {}W {}D {}L",
+                name,
+                tally.total(),
+                tally.shape_points,
+                tally.outcome_points,
+                tally.wins,
+                tally.draws,
+                tally.losses
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{} of {} rounds change outcome (*)",
+            self.changed_rounds().len(),
+            self.rounds.len()
+        );
+        out
+    }
+}
@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+
+use crate::Outcome;
+
+pub const CLASSIC_SPEC: &str = "\
+# Rock-paper-scissors, as described in the puzzle
+weapon Rock A X 1
+weapon Paper B Y 2
+weapon Scissors C Z 3
+cyclic
+outcome X lose
+outcome Y draw
+outcome Z win
+";
+
+pub const RPSLS_SPEC: &str = "\
+# Rock-paper-scissors-lizard-Spock
+weapon Rock A X 1
+weapon Paper B Y 2
+weapon Scissors C Z 3
+weapon Lizard D V 4
+weapon Spock E W 5
+beats Rock Scissors Lizard
+beats Paper Rock Spock
+beats Scissors Paper Lizard
+beats Lizard Paper Spock
+beats Spock Rock Scissors
+outcome X lose
+outcome Y draw
+outcome Z win
+outcome V lose
+outcome W win
+";
+
+lazy_static! {
+    pub static ref CLASSIC: RuleSet = CLASSIC_SPEC.parse().unwrap();
+}
+
+/// A weapon, identified by its position in a `RuleSet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Weapon(pub usize);
+
+#[derive(Debug, PartialEq)]
+pub struct RuleSetError {
+    pub line: usize,
+    msg: String,
+}
+
+impl fmt::Display for RuleSetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.msg)
+        } else {
+            write!(f, "line {}: {}", self.line, self.msg)
+        }
+    }
+}
+
+impl Error for RuleSetError {}
+
+impl RuleSetError {
+    pub fn new(line: usize, msg: &str) -> Self {
+        RuleSetError {
+            line,
+            msg: String::from(msg),
+        }
+    }
+}
+
+/// Weapons, their symbols and values, and which weapon beats which.
+///
+/// Each weapon has one symbol for the opponent's column and one for ours. Our
+/// symbols can also be read as a desired outcome (part 2 of the puzzle), so
+/// every one of them carries an outcome too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSet {
+    names: Vec<String>,
+    values: Vec<i32>,
+    opponent_symbols: Vec<String>,
+    player_symbols: Vec<String>,
+    outcomes: Vec<Outcome>,
+    beats: Vec<Vec<bool>>,
+}
+
+impl RuleSet {
+    pub fn classic() -> RuleSet {
+        CLASSIC.clone()
+    }
+
+    pub fn rpsls() -> RuleSet {
+        RPSLS_SPEC.parse().unwrap()
+    }
+
+    pub fn from_path(path: &str) -> Result<RuleSet, RuleSetError> {
+        let spec = fs::read_to_string(path)
+            .map_err(|e| RuleSetError::new(0, &format!("could not read {}: {}", path, e)))?;
+        spec.parse()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn weapons(&self) -> impl Iterator<Item = Weapon> {
+        (0..self.len()).map(Weapon)
+    }
+
+    pub fn name(&self, weapon: Weapon) -> &str {
+        &self.names[weapon.0]
+    }
+
+    pub fn value(&self, weapon: Weapon) -> i32 {
+        self.values[weapon.0]
+    }
+
+    pub fn opponent_symbol(&self, weapon: Weapon) -> &str {
+        &self.opponent_symbols[weapon.0]
+    }
+
+    pub fn player_symbol(&self, weapon: Weapon) -> &str {
+        &self.player_symbols[weapon.0]
+    }
+
+    pub fn weapon_by_name(&self, name: &str) -> Option<Weapon> {
+        self.names.iter().position(|n| n == name).map(Weapon)
+    }
+
+    pub fn parse_opponent(&self, symbol: &str) -> Option<Weapon> {
+        self.opponent_symbols
+            .iter()
+            .position(|s| s == symbol)
+            .map(Weapon)
+    }
+
+    pub fn parse_player(&self, symbol: &str) -> Option<Weapon> {
+        self.player_symbols
+            .iter()
+            .position(|s| s == symbol)
+            .map(Weapon)
+    }
+
+    /// The outcome our symbol for `weapon` asks for when read as in part 2.
+    pub fn symbol_outcome(&self, weapon: Weapon) -> Outcome {
+        self.outcomes[weapon.0]
+    }
+
+    pub fn beats(&self, a: Weapon, b: Weapon) -> bool {
+        self.beats[a.0][b.0]
+    }
+
+    pub fn outcome(&self, mine: Weapon, theirs: Weapon) -> Outcome {
+        if self.beats(mine, theirs) {
+            Outcome::Win
+        } else if self.beats(theirs, mine) {
+            Outcome::Lose
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    /// The weapon to play against `theirs` to get `desired`. When several
+    /// weapons qualify the most valuable one is picked.
+    pub fn desired(&self, theirs: Weapon, desired: Outcome) -> Weapon {
+        self.weapons()
+            .filter(|&w| self.outcome(w, theirs) == desired)
+            .max_by_key(|&w| (self.value(w), std::cmp::Reverse(w)))
+            .expect("Validated rule sets can reach every outcome.")
+    }
+
+    fn validate(&self) -> Result<(), RuleSetError> {
+        let n = self.len();
+        if n < 3 {
+            return Err(RuleSetError::new(0, "need at least three weapons"));
+        }
+
+        for a in 0..n {
+            if self.beats[a][a] {
+                return Err(RuleSetError::new(
+                    0,
+                    &format!("{} cannot beat itself", self.names[a]),
+                ));
+            }
+            for b in (a + 1)..n {
+                if self.beats[a][b] == self.beats[b][a] {
+                    return Err(RuleSetError::new(
+                        0,
+                        &format!(
+                            "exactly one of {} and {} must beat the other",
+                            self.names[a], self.names[b]
+                        ),
+                    ));
+                }
+            }
+
+            let wins = (0..n).filter(|&b| self.beats[a][b]).count();
+            if wins == 0 || wins == n - 1 {
+                return Err(RuleSetError::new(
+                    0,
+                    &format!("{} must both beat and lose to something", self.names[a]),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = RuleSetError;
+
+    /// Parse a rule set spec. Each non-empty line, minus `#` comments, is one
+    /// of
+    ///
+    /// ```text
+    /// weapon <name> <opponent symbol> <player symbol> <value>
+    /// beats <winner> <loser>...
+    /// cyclic
+    /// outcome <player symbol> lose|draw|win
+    /// ```
+    ///
+    /// `cyclic` makes each weapon beat the (n-1)/2 weapons listed just before
+    /// it, wrapping around. Outcomes may be left out for three-weapon games, in
+    /// which case our symbols read as lose, draw, win in order.
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut rules = RuleSet {
+            names: vec![],
+            values: vec![],
+            opponent_symbols: vec![],
+            player_symbols: vec![],
+            outcomes: vec![],
+            beats: vec![],
+        };
+        let mut beats: Vec<(usize, String, String)> = vec![];
+        let mut outcomes: HashMap<String, Outcome> = HashMap::new();
+        let mut cyclic = false;
+
+        for (idx, line) in spec.lines().enumerate() {
+            let line_num = idx + 1;
+            let line = line.split('#').next().unwrap();
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let err = |msg: &str| RuleSetError::new(line_num, msg);
+
+            match tokens.as_slice() {
+                [] => continue,
+                ["weapon", name, opp, player, value] => {
+                    if rules.names.iter().any(|n| n == name) {
+                        return Err(err(&format!("duplicate weapon {}", name)));
+                    }
+                    if rules.opponent_symbols.iter().any(|s| s == opp)
+                        || rules.player_symbols.iter().any(|s| s == player)
+                    {
+                        return Err(err("duplicate symbol"));
+                    }
+                    let value = value
+                        .parse()
+                        .map_err(|_| err(&format!("invalid value {:?}", value)))?;
+
+                    rules.names.push(String::from(*name));
+                    rules.opponent_symbols.push(String::from(*opp));
+                    rules.player_symbols.push(String::from(*player));
+                    rules.values.push(value);
+                }
+                ["beats", winner, losers @ ..] if !losers.is_empty() => {
+                    for loser in losers {
+                        beats.push((line_num, String::from(*winner), String::from(*loser)));
+                    }
+                }
+                ["cyclic"] => cyclic = true,
+                ["outcome", symbol, outcome] => {
+                    let outcome = match *outcome {
+                        "lose" => Outcome::Lose,
+                        "draw" => Outcome::Draw,
+                        "win" => Outcome::Win,
+                        _ => return Err(err(&format!("invalid outcome {:?}", outcome))),
+                    };
+                    outcomes.insert(String::from(*symbol), outcome);
+                }
+                _ => return Err(err(&format!("could not parse {:?}", line.trim()))),
+            }
+        }
+
+        let n = rules.len();
+        rules.beats = vec![vec![false; n]; n];
+        if cyclic {
+            for a in 0..n {
+                for step in 1..=(n - 1) / 2 {
+                    rules.beats[a][(a + n - step) % n] = true;
+                }
+            }
+        }
+        for (line_num, winner, loser) in beats {
+            let lookup = |name: &str| {
+                rules
+                    .weapon_by_name(name)
+                    .ok_or_else(|| RuleSetError::new(line_num, &format!("unknown weapon {}", name)))
+            };
+            let (a, b) = (lookup(&winner)?, lookup(&loser)?);
+            rules.beats[a.0][b.0] = true;
+        }
+
+        if outcomes.is_empty() && n == 3 {
+            rules.outcomes = vec![Outcome::Lose, Outcome::Draw, Outcome::Win];
+        } else {
+            if let Some(symbol) = outcomes.keys().find(|s| rules.parse_player(s).is_none()) {
+                return Err(RuleSetError::new(
+                    0,
+                    &format!("outcome given for unknown symbol {}", symbol),
+                ));
+            }
+            rules.outcomes = rules
+                .player_symbols
+                .iter()
+                .map(|s| {
+                    outcomes.get(s).copied().ok_or_else(|| {
+                        RuleSetError::new(0, &format!("no outcome given for symbol {}", s))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+        }
+
+        rules.validate()?;
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classic() {
+        let rules = RuleSet::classic();
+        let (rock, paper, scissors) = (Weapon(0), Weapon(1), Weapon(2));
+
+        assert_eq!(rules.outcome(paper, rock), Outcome::Win);
+        assert_eq!(rules.outcome(rock, paper), Outcome::Lose);
+        assert_eq!(rules.outcome(scissors, scissors), Outcome::Draw);
+        assert_eq!(rules.desired(rock, Outcome::Win), paper);
+        assert_eq!(rules.desired(rock, Outcome::Lose), scissors);
+    }
+
+    #[test]
+    fn test_rpsls() {
+        let rules = RuleSet::rpsls();
+        let spock = rules.weapon_by_name("Spock").unwrap();
+        let lizard = rules.weapon_by_name("Lizard").unwrap();
+        let scissors = rules.weapon_by_name("Scissors").unwrap();
+
+        assert_eq!(rules.outcome(lizard, spock), Outcome::Win);
+        assert_eq!(rules.outcome(spock, scissors), Outcome::Win);
+
+        // Both Scissors and Rock beat Lizard, Rock is worth less
+        assert_eq!(rules.desired(lizard, Outcome::Win), scissors);
+    }
+
+    #[test]
+    fn test_cyclic_matches_explicit() {
+        let spec = "weapon Rock A V 1\nweapon Spock B W 2\nweapon Paper C X 3\n\
+                    weapon Lizard D Y 4\nweapon Scissors E Z 5\ncyclic\n\
+                    outcome V lose\noutcome W lose\noutcome X draw\noutcome Y win\noutcome Z win\n";
+        let cyclic: RuleSet = spec.parse().unwrap();
+        let rpsls = RuleSet::rpsls();
+
+        for a in rpsls.weapons() {
+            for b in rpsls.weapons() {
+                let ca = cyclic.weapon_by_name(rpsls.name(a)).unwrap();
+                let cb = cyclic.weapon_by_name(rpsls.name(b)).unwrap();
+                assert_eq!(cyclic.beats(ca, cb), rpsls.beats(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        let err = "weapon Rock A X 1\nweapon Paper B Y 2\n"
+            .parse::<RuleSet>()
+            .unwrap_err();
+        assert_eq!(err.line, 0);
+
+        let err = "weapon Rock A X 1\nbeats Rock Paper\n"
+            .parse::<RuleSet>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+
+        // With an even number of weapons, `cyclic` leaves opposite ones tied
+        let err = "weapon A A W 1\nweapon B B X 1\nweapon C C Y 1\nweapon D D Z 1\ncyclic\n"
+            .parse::<RuleSet>()
+            .unwrap_err();
+        assert_eq!(err.line, 0);
+    }
+}
@@ -0,0 +1,220 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::decoder::{Reading, TableDecoder};
+use crate::rules::{RuleSet, Weapon};
+use crate::{Outcome, RockPaperScissors};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    Maximize,
+    Minimize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub decoder: TableDecoder,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MappingReport {
+    pub objective: Objective,
+    /// Best first. The first entry is the answer.
+    pub ranked: Vec<Mapping>,
+}
+
+impl Mapping {
+    /// Render as e.g. `X=Rock Y=draw Z=Paper`.
+    pub fn describe(&self, rules: &RuleSet) -> String {
+        let parts: Vec<String> = self
+            .decoder
+            .readings()
+            .iter()
+            .enumerate()
+            .map(|(idx, reading)| {
+                let meaning = match reading {
+                    Reading::Shape(w) => String::from(rules.name(*w)),
+                    Reading::Outcome(o) => format!("{:?}", o).to_lowercase(),
+                };
+                format!("{}={}", rules.player_symbol(Weapon(idx)), meaning)
+            })
+            .collect();
+        parts.join(" ")
+    }
+}
+
+impl MappingReport {
+    pub fn best(&self) -> Option<&Mapping> {
+        self.ranked.first()
+    }
+}
+
+struct Search<'a> {
+    /// `gain[symbol][candidate]`, already negated when minimizing
+    gain: &'a [Vec<i64>],
+    limit: usize,
+    used: Vec<bool>,
+    chosen: Vec<usize>,
+    kept: BinaryHeap<Reverse<(i64, Vec<usize>)>>,
+}
+
+impl Search<'_> {
+    fn threshold(&self) -> Option<i64> {
+        if self.kept.len() < self.limit {
+            None
+        } else {
+            self.kept.peek().map(|Reverse((score, _))| *score)
+        }
+    }
+
+    /// Optimistic total for the symbols not yet assigned.
+    fn bound(&self, from: usize) -> i64 {
+        self.gain[from..]
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(c, _)| !self.used[*c])
+                    .map(|(_, g)| *g)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .sum()
+    }
+
+    fn run(&mut self, symbol: usize, score: i64) {
+        if symbol == self.gain.len() {
+            self.kept.push(Reverse((score, self.chosen.clone())));
+            if self.kept.len() > self.limit {
+                self.kept.pop();
+            }
+            return;
+        }
+
+        if let Some(threshold) = self.threshold() {
+            if score + self.bound(symbol) <= threshold {
+                return;
+            }
+        }
+
+        for cand in 0..self.used.len() {
+            if self.used[cand] {
+                continue;
+            }
+            self.used[cand] = true;
+            self.chosen.push(cand);
+            self.run(symbol + 1, score + self.gain[symbol][cand]);
+            self.chosen.pop();
+            self.used[cand] = false;
+        }
+    }
+}
+
+/// The best `limit` bijections from our symbols to `candidates`, as
+/// `(score, candidate index per symbol)`.
+fn search_bijections(gain: &[Vec<i64>], limit: usize) -> Vec<(i64, Vec<usize>)> {
+    let mut search = Search {
+        gain,
+        limit,
+        used: vec![false; gain.first().map_or(0, |g| g.len())],
+        chosen: vec![],
+        kept: BinaryHeap::new(),
+    };
+    search.run(0, 0);
+    search.kept.into_iter().map(|Reverse(k)| k).collect()
+}
+
+/// Try every way of reading our column as a one-to-one map onto shapes, and,
+/// for three-symbol games, onto outcomes. Returns the `limit` best mappings
+/// for `objective`.
+///
+/// A mapping's score is a sum of independent per-symbol terms, so the search
+/// only needs the round counts for each (opponent, symbol) pair. It's a
+/// branch and bound over symbols: a branch is dropped once even the best
+/// remaining choices can't beat the worst mapping kept so far, which keeps
+/// large rule sets tractable when `limit` is small.
+pub fn infer_mapping(
+    rules: &RuleSet,
+    guide: &[RockPaperScissors],
+    objective: Objective,
+    limit: usize,
+) -> MappingReport {
+    let n = rules.len();
+    let mut counts = vec![vec![0i64; n]; n];
+    for round in guide {
+        counts[round.p2().0][round.p1().0] += 1;
+    }
+
+    let sign = match objective {
+        Objective::Maximize => 1,
+        Objective::Minimize => -1,
+    };
+    let gain_for = |readings: &[Reading]| -> Vec<Vec<i64>> {
+        (0..n)
+            .map(|symbol| {
+                readings
+                    .iter()
+                    .map(|reading| {
+                        rules
+                            .weapons()
+                            .map(|opp| {
+                                let mine = match *reading {
+                                    Reading::Shape(w) => w,
+                                    Reading::Outcome(o) => rules.desired(opp, o),
+                                };
+                                let round = RockPaperScissors::new(opp, mine);
+                                counts[symbol][opp.0] * round.score_p2(rules) as i64
+                            })
+                            .sum::<i64>()
+                            * sign
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+
+    let mut families: Vec<Vec<Reading>> = vec![rules.weapons().map(Reading::Shape).collect()];
+    if n == 3 {
+        families.push(
+            [Outcome::Lose, Outcome::Draw, Outcome::Win]
+                .into_iter()
+                .map(Reading::Outcome)
+                .collect(),
+        );
+    }
+
+    let mut ranked: Vec<(i64, usize, Vec<usize>)> = vec![];
+    for (family, readings) in families.iter().enumerate() {
+        let gain = gain_for(readings);
+        for (score, chosen) in search_bijections(&gain, limit) {
+            ranked.push((score, family, chosen));
+        }
+    }
+
+    // Ties go to shapes before outcomes, then to candidate order
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| (a.1, &a.2).cmp(&(b.1, &b.2))));
+    ranked.truncate(limit);
+
+    MappingReport {
+        objective,
+        ranked: ranked
+            .into_iter()
+            .map(|(score, family, chosen)| Mapping {
+                decoder: TableDecoder::from_readings(
+                    chosen.into_iter().map(|c| families[family][c]).collect(),
+                ),
+                score: (score * sign) as i32,
+            })
+            .collect(),
+    }
+}
+
+/// Convenience for small games: every mapping, best first.
+pub fn rank_all_mappings(
+    rules: &RuleSet,
+    guide: &[RockPaperScissors],
+    objective: Objective,
+) -> MappingReport {
+    infer_mapping(rules, guide, objective, usize::MAX)
+}
@@ -0,0 +1,289 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::decoder::ColumnDecoder;
+use crate::rules::{RuleSet, Weapon};
+use crate::{Outcome, RockPaperScissors, State};
+
+const STATES: [State; 3] = [State::Rock, State::Paper, State::Scissors];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StrategyError {
+    EmptySequence,
+    EmptyGuide,
+    /// The rule set picked a weapon outside rock, paper and scissors, which
+    /// is all a tournament plays.
+    UnplayableWeapon(String),
+}
+
+impl fmt::Display for StrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StrategyError::EmptySequence => write!(f, "cyclic strategy needs a sequence"),
+            StrategyError::EmptyGuide => write!(f, "cannot replay an empty guide"),
+            StrategyError::UnplayableWeapon(name) => {
+                write!(f, "{} cannot be played in a tournament", name)
+            }
+        }
+    }
+}
+
+impl Error for StrategyError {}
+
+/// The tournament shape for `weapon` under `rules`.
+fn playable(rules: &RuleSet, weapon: Weapon) -> Result<State, StrategyError> {
+    State::from_weapon(weapon)
+        .ok_or_else(|| StrategyError::UnplayableWeapon(String::from(rules.name(weapon))))
+}
+
+/// Small deterministic generator (SplitMix64), so tournaments can be
+/// reproduced from a seed without pulling in a dependency.
+#[derive(Debug, Clone)]
+pub struct SeededRng(u64);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
+
+pub trait Strategy {
+    fn name(&self) -> String;
+
+    /// Pick a shape for the next round of the current match.
+    fn play(&mut self) -> State;
+
+    /// Learn what happened in the round just played.
+    fn observe(&mut self, _mine: State, _theirs: State, _outcome: Outcome) {}
+
+    /// Forget everything from the previous match.
+    fn reset(&mut self) {}
+}
+
+pub struct ConstantStrategy(pub State);
+
+impl Strategy for ConstantStrategy {
+    fn name(&self) -> String {
+        format!("always-{:?}", self.0).to_lowercase()
+    }
+
+    fn play(&mut self) -> State {
+        self.0
+    }
+}
+
+pub struct CyclicStrategy {
+    sequence: Vec<State>,
+    pos: usize,
+}
+
+impl CyclicStrategy {
+    pub fn new(sequence: Vec<State>) -> Result<Self, StrategyError> {
+        if sequence.is_empty() {
+            return Err(StrategyError::EmptySequence);
+        }
+        Ok(CyclicStrategy { sequence, pos: 0 })
+    }
+}
+
+impl Strategy for CyclicStrategy {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.sequence.iter().map(|s| format!("{:?}", s)).collect();
+        format!("cycle-{}", names.join("-")).to_lowercase()
+    }
+
+    fn play(&mut self) -> State {
+        let state = self.sequence[self.pos];
+        self.pos = (self.pos + 1) % self.sequence.len();
+        state
+    }
+
+    fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
+
+/// Uniformly random shapes. Every match replays the same sequence.
+pub struct RandomStrategy {
+    seed: u64,
+    rng: SeededRng,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        RandomStrategy {
+            seed,
+            rng: SeededRng::new(seed),
+        }
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn name(&self) -> String {
+        format!("random-{}", self.seed)
+    }
+
+    fn play(&mut self) -> State {
+        STATES[self.rng.below(STATES.len())]
+    }
+
+    fn reset(&mut self) {
+        self.rng = SeededRng::new(self.seed);
+    }
+}
+
+/// Plays whatever beats the opponent's most frequent shape so far. Ties,
+/// including the opening move, are broken in rock-paper-scissors order.
+pub struct FrequencyStrategy {
+    counts: [usize; 3],
+    /// What to play against each shape.
+    counters: [State; 3],
+}
+
+impl FrequencyStrategy {
+    /// Counter shapes as `rules` would pick them.
+    pub fn new(rules: &RuleSet) -> Result<Self, StrategyError> {
+        let mut counters = STATES;
+        for (counter, state) in counters.iter_mut().zip(STATES) {
+            *counter = playable(rules, rules.desired(state.weapon(), Outcome::Win))?;
+        }
+        Ok(FrequencyStrategy {
+            counts: [0; 3],
+            counters,
+        })
+    }
+}
+
+impl Default for FrequencyStrategy {
+    /// Counter shapes under the classic rules.
+    fn default() -> Self {
+        FrequencyStrategy {
+            counts: [0; 3],
+            counters: [State::Paper, State::Scissors, State::Rock],
+        }
+    }
+}
+
+impl Strategy for FrequencyStrategy {
+    fn name(&self) -> String {
+        String::from("frequency")
+    }
+
+    fn play(&mut self) -> State {
+        let favourite = (0..STATES.len())
+            .rev()
+            .max_by_key(|&idx| self.counts[idx])
+            .unwrap();
+        self.counters[favourite]
+    }
+
+    fn observe(&mut self, _mine: State, theirs: State, _outcome: Outcome) {
+        self.counts[theirs as usize] += 1;
+    }
+
+    fn reset(&mut self) {
+        self.counts = [0; 3];
+    }
+}
+
+/// Replays the shapes a strategy guide tells us to play, looping at the end.
+pub struct ReplayStrategy {
+    name: String,
+    moves: Vec<State>,
+    pos: usize,
+}
+
+impl ReplayStrategy {
+    /// Our moves from `guide`, read with `decoder` under `rules`, which
+    /// should be the rules the guide was parsed with. Outcome readings are
+    /// resolved against the opponent written in the guide, not the one we
+    /// end up facing.
+    pub fn from_guide(
+        name: &str,
+        rules: &RuleSet,
+        guide: &[RockPaperScissors],
+        decoder: &dyn ColumnDecoder,
+    ) -> Result<Self, StrategyError> {
+        if guide.is_empty() {
+            return Err(StrategyError::EmptyGuide);
+        }
+        let moves = guide
+            .iter()
+            .map(|round| playable(rules, decoder.decode(rules, round)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(ReplayStrategy {
+            name: String::from(name),
+            moves,
+            pos: 0,
+        })
+    }
+}
+
+impl Strategy for ReplayStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn play(&mut self) -> State {
+        let state = self.moves[self.pos];
+        self.pos = (self.pos + 1) % self.moves.len();
+        state
+    }
+
+    fn reset(&mut self) {
+        self.pos = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_is_reproducible() {
+        let mut a = RandomStrategy::new(7);
+        let first: Vec<State> = (0..20).map(|_| a.play()).collect();
+        a.reset();
+        let second: Vec<State> = (0..20).map(|_| a.play()).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_frequency_counters() {
+        let mut freq = FrequencyStrategy::default();
+        assert_eq!(freq.play(), State::Paper);
+
+        freq.observe(State::Rock, State::Scissors, Outcome::Win);
+        freq.observe(State::Rock, State::Scissors, Outcome::Win);
+        freq.observe(State::Rock, State::Paper, Outcome::Lose);
+        assert_eq!(freq.play(), State::Rock);
+
+        let classic = FrequencyStrategy::new(&RuleSet::classic()).unwrap();
+        assert_eq!(classic.counters, freq.counters);
+
+        // Spock beats rock and is worth the most
+        assert_eq!(
+            FrequencyStrategy::new(&RuleSet::rpsls()).err(),
+            Some(StrategyError::UnplayableWeapon(String::from("Spock")))
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::strategy::{SeededRng, Strategy};
+use crate::{match_value, Outcome};
+
+const WIN_POINTS: u32 = 3;
+const DRAW_POINTS: u32 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+    pub byes: u32,
+    /// Match points: a win or a bye is worth 3, a draw 1.
+    pub points: u32,
+    /// Puzzle score summed over every round played.
+    pub score: i32,
+    pub rounds_won: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchResult {
+    pub players: (usize, usize),
+    pub scores: (i32, i32),
+    pub rounds_won: (u32, u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leaderboard {
+    pub standings: Vec<Standing>,
+}
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:<26} {:>4} {:>4} {:>4} {:>4} {:>6} {:>8}",
+            "rank", "strategy", "W", "D", "L", "bye", "points", "score"
+        )?;
+        for (rank, s) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<26} {:>4} {:>4} {:>4} {:>4} {:>6} {:>8}",
+                rank + 1,
+                s.name,
+                s.won,
+                s.drawn,
+                s.lost,
+                s.byes,
+                s.points,
+                s.score
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Tournament {
+    players: Vec<Box<dyn Strategy>>,
+    standings: Vec<Standing>,
+    rounds_per_match: usize,
+}
+
+impl Tournament {
+    pub fn new(rounds_per_match: usize) -> Self {
+        Tournament {
+            players: vec![],
+            standings: vec![],
+            rounds_per_match,
+        }
+    }
+
+    pub fn add(&mut self, player: Box<dyn Strategy>) {
+        self.standings.push(Standing {
+            name: player.name(),
+            ..Default::default()
+        });
+        self.players.push(player);
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// Play `a` against `b` and record the result in the standings.
+    pub fn play_match(&mut self, a: usize, b: usize) -> MatchResult {
+        assert!(a != b, "A strategy cannot play itself.");
+        let (lo, hi) = (a.min(b), a.max(b));
+        let (left, right) = self.players.split_at_mut(hi);
+        let (p_lo, p_hi) = (&mut left[lo], &mut right[0]);
+
+        p_lo.reset();
+        p_hi.reset();
+
+        let mut scores = (0, 0);
+        let mut rounds_won = (0, 0);
+        for _ in 0..self.rounds_per_match {
+            let (s_lo, s_hi) = (p_lo.play(), p_hi.play());
+            let (o_lo, o_hi) = (s_lo.score_versus(s_hi), s_hi.score_versus(s_lo));
+
+            scores.0 += s_lo.value() + match_value(o_lo);
+            scores.1 += s_hi.value() + match_value(o_hi);
+            match o_lo {
+                Outcome::Win => rounds_won.0 += 1,
+                Outcome::Lose => rounds_won.1 += 1,
+                Outcome::Draw => {}
+            }
+
+            p_lo.observe(s_lo, s_hi, o_lo);
+            p_hi.observe(s_hi, s_lo, o_hi);
+        }
+
+        let result = if a == lo {
+            MatchResult {
+                players: (a, b),
+                scores,
+                rounds_won,
+            }
+        } else {
+            MatchResult {
+                players: (a, b),
+                scores: (scores.1, scores.0),
+                rounds_won: (rounds_won.1, rounds_won.0),
+            }
+        };
+        self.record(&result);
+        result
+    }
+
+    fn record(&mut self, result: &MatchResult) {
+        let (a, b) = result.players;
+        self.standings[a].score += result.scores.0;
+        self.standings[b].score += result.scores.1;
+        self.standings[a].rounds_won += result.rounds_won.0;
+        self.standings[b].rounds_won += result.rounds_won.1;
+
+        let (winner, loser) = match result.rounds_won.0.cmp(&result.rounds_won.1) {
+            std::cmp::Ordering::Greater => (a, b),
+            std::cmp::Ordering::Less => (b, a),
+            std::cmp::Ordering::Equal => {
+                for p in [a, b] {
+                    self.standings[p].drawn += 1;
+                    self.standings[p].points += DRAW_POINTS;
+                }
+                return;
+            }
+        };
+        self.standings[winner].won += 1;
+        self.standings[winner].points += WIN_POINTS;
+        self.standings[loser].lost += 1;
+    }
+
+    /// Standings so far, best first: by match points, then total score, then
+    /// name.
+    pub fn leaderboard(&self) -> Leaderboard {
+        let mut standings = self.standings.clone();
+        standings.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then(b.score.cmp(&a.score))
+                .then(a.name.cmp(&b.name))
+        });
+        Leaderboard { standings }
+    }
+
+    /// Everyone plays everyone once.
+    pub fn round_robin(&mut self) -> Leaderboard {
+        for a in 0..self.len() {
+            for b in (a + 1)..self.len() {
+                self.play_match(a, b);
+            }
+        }
+        self.leaderboard()
+    }
+
+    /// `num_rounds` of Swiss pairing: each round pairs players with similar
+    /// standings who haven't met yet. The seed orders the first round. With
+    /// an odd field the lowest-ranked player without a bye sits out and gets
+    /// a win's worth of points.
+    pub fn swiss(&mut self, num_rounds: usize, seed: u64) -> Leaderboard {
+        let mut rng = SeededRng::new(seed);
+        let mut seeding: Vec<usize> = (0..self.len()).collect();
+        rng.shuffle(&mut seeding);
+
+        let mut played: HashSet<(usize, usize)> = HashSet::new();
+        for _ in 0..num_rounds {
+            let mut order: Vec<usize> = (0..self.len()).collect();
+            order.sort_by_key(|&p| {
+                let s = &self.standings[p];
+                (
+                    std::cmp::Reverse(s.points),
+                    std::cmp::Reverse(s.score),
+                    seeding.iter().position(|&x| x == p),
+                )
+            });
+
+            if order.len() % 2 == 1 {
+                let bye_pos = order
+                    .iter()
+                    .rposition(|&p| self.standings[p].byes == 0)
+                    .unwrap_or(order.len() - 1);
+                let bye = order.remove(bye_pos);
+                self.standings[bye].byes += 1;
+                self.standings[bye].points += WIN_POINTS;
+            }
+
+            while let Some(a) = order.first().copied() {
+                order.remove(0);
+                let b_pos = order
+                    .iter()
+                    .position(|&b| !played.contains(&(a.min(b), a.max(b))))
+                    .unwrap_or(0);
+                let b = order.remove(b_pos);
+
+                played.insert((a.min(b), a.max(b)));
+                self.play_match(a, b);
+            }
+        }
+
+        self.leaderboard()
+    }
+}
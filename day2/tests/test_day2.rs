@@ -1,26 +1,289 @@
 #[cfg(test)]
 mod tests {
+    use day2::decoder::*;
+    use day2::report::*;
+    use day2::rules::*;
+    use day2::solver::*;
+    use day2::strategy::*;
+    use day2::tournament::*;
     use day2::*;
 
+    use std::io::Cursor;
+
     #[test]
     fn test_rps_parse() {
-        assert_eq!(State::parse("A").value(), 1);
-        assert_eq!(State::parse("B").value(), 2);
-        assert_eq!(State::parse("C").value(), 3);
-        assert_eq!(State::parse("X").value(), 1);
-        assert_eq!(State::parse("Y").value(), 2);
-        assert_eq!(State::parse("Z").value(), 3);
+        assert_eq!(State::parse("A").unwrap().value(), 1);
+        assert_eq!(State::parse("B").unwrap().value(), 2);
+        assert_eq!(State::parse("C").unwrap().value(), 3);
+        assert_eq!(State::parse("X").unwrap().value(), 1);
+        assert_eq!(State::parse("Y").unwrap().value(), 2);
+        assert_eq!(State::parse("Z").unwrap().value(), 3);
+        assert!(State::parse("Q").is_none());
     }
 
     #[test]
     fn test_day2_pt1() {
-        let guide = parse_strategy_guide("input_test.txt");
+        let guide = parse_strategy_file("input_test.txt").unwrap();
         assert_eq!(score_strategy_guide(&guide), 15);
     }
 
     #[test]
     fn test_day2_pt2() {
-        let guide = parse_strategy_guide("input_test.txt");
+        let guide = parse_strategy_file("input_test.txt").unwrap();
         assert_eq!(score_strategy_guide_pt2(&guide), 12);
     }
+
+    #[test]
+    fn test_parse_error_position() {
+        let err = parse_strategy_guide(Cursor::new("A Y\nB  Q\nC Z\n")).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.column, 4);
+        assert_eq!(err.token, "Q");
+        assert!(matches!(err.kind, GuideErrorKind::UnknownSymbol));
+
+        let err = parse_strategy_guide(Cursor::new("A\n")).unwrap_err();
+        assert!(matches!(err.kind, GuideErrorKind::MissingSymbol));
+
+        let err = parse_strategy_guide(Cursor::new("A Y Z\n")).unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (5, "Z"));
+        assert!(matches!(err.kind, GuideErrorKind::ExtraSymbol));
+
+        let err = parse_strategy_file("missing.txt").unwrap_err();
+        assert!(matches!(err.kind, GuideErrorKind::Open(_)));
+        assert!(err.to_string().starts_with("could not open missing.txt: "));
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let (guide, errors) =
+            parse_strategy_guide_lenient(Cursor::new("A Y\nD X\nB X\n\nC\nC Z\n"));
+        assert_eq!(score_strategy_guide(&guide), 15);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(errors[1].line, 5);
+    }
+
+    #[test]
+    fn test_rpsls_guide() {
+        let rules = RuleSet::rpsls();
+        let guide = parse_strategy_guide_with(&rules, Cursor::new("A W\nE V\nD Z\n")).unwrap();
+
+        // Spock beats Rock, Lizard beats Spock, Scissors beats Lizard
+        assert_eq!(
+            score_strategy_guide_with(&rules, &guide, &ShapeDecoder),
+            (5 + 6) + (4 + 6) + (3 + 6)
+        );
+
+        // Win against Rock with Spock, lose against Spock with Scissors, win
+        // against Lizard with Scissors
+        assert_eq!(
+            score_strategy_guide_with(&rules, &guide, &OutcomeDecoder),
+            (5 + 6) + 3 + (3 + 6)
+        );
+    }
+
+    #[test]
+    fn test_custom_decoders() {
+        let rules = RuleSet::classic();
+        let guide = parse_strategy_file("input_test.txt").unwrap();
+
+        let as_shapes = TableDecoder::parse(&rules, "X Rock\nY Paper\nZ Scissors\n").unwrap();
+        let as_outcomes = TableDecoder::parse(&rules, "X lose\nY draw\nZ win\n").unwrap();
+        assert_eq!(score_strategy_guide_with(&rules, &guide, &as_shapes), 15);
+        assert_eq!(score_strategy_guide_with(&rules, &guide, &as_outcomes), 12);
+
+        // X draws, Y wins, Z loses: A Y -> Paper, B X -> Paper, C Z -> Paper
+        let shift = ShiftDecoder { steps: 0 };
+        assert_eq!(score_strategy_guide_with(&rules, &guide, &shift), 8 + 5 + 2);
+    }
+
+    #[test]
+    fn test_infer_mapping_classic() {
+        let rules = RuleSet::classic();
+        let guide = parse_strategy_file("input_test.txt").unwrap();
+
+        let report = rank_all_mappings(&rules, &guide, Objective::Maximize);
+        assert_eq!(report.ranked.len(), 12);
+        for mapping in report.ranked.iter() {
+            assert_eq!(
+                mapping.score,
+                score_strategy_guide_with(&rules, &guide, &mapping.decoder)
+            );
+        }
+        assert!(report.ranked.windows(2).all(|w| w[0].score >= w[1].score));
+
+        // Always win: A Y -> Paper, B X -> Scissors, C Z -> Rock
+        let best = report.best().unwrap();
+        assert_eq!(best.score, 8 + 9 + 7);
+        assert_eq!(best.describe(&rules), "X=Scissors Y=Paper Z=Rock");
+
+        let worst = rank_all_mappings(&rules, &guide, Objective::Minimize);
+        assert_eq!(worst.best().unwrap().score, report.ranked[11].score);
+    }
+
+    #[test]
+    fn test_infer_mapping_pruned() {
+        let mut spec = String::new();
+        for (idx, name) in ["a", "b", "c", "d", "e", "f", "g"].iter().enumerate() {
+            spec.push_str(&format!(
+                "weapon {} {} {} {}\n",
+                name,
+                idx,
+                name.to_uppercase(),
+                idx + 1
+            ));
+        }
+        spec.push_str("cyclic\n");
+        for (symbol, outcome) in [("A", "lose"), ("B", "lose"), ("C", "lose"), ("D", "draw")] {
+            spec.push_str(&format!("outcome {} {}\n", symbol, outcome));
+        }
+        for symbol in ["E", "F", "G"] {
+            spec.push_str(&format!("outcome {} win\n", symbol));
+        }
+        let rules: RuleSet = spec.parse().unwrap();
+
+        let rounds: String = (0..60)
+            .map(|i| {
+                format!(
+                    "{} {}\n",
+                    (i * 5) % 7,
+                    ["A", "B", "C", "D", "E", "F", "G"][(i * 3) % 7]
+                )
+            })
+            .collect();
+        let guide = parse_strategy_guide_with(&rules, Cursor::new(rounds)).unwrap();
+
+        let all = rank_all_mappings(&rules, &guide, Objective::Maximize);
+        assert_eq!(all.ranked.len(), 5040);
+
+        let top = infer_mapping(&rules, &guide, Objective::Maximize, 3);
+        let top_scores: Vec<i32> = top.ranked.iter().map(|m| m.score).collect();
+        let all_scores: Vec<i32> = all.ranked.iter().take(3).map(|m| m.score).collect();
+        assert_eq!(top_scores, all_scores);
+        assert_eq!(
+            top.best().unwrap().score,
+            score_strategy_guide_with(&rules, &guide, &top.best().unwrap().decoder)
+        );
+    }
+
+    fn roster(seed: u64) -> Tournament {
+        let guide = parse_strategy_file("input_test.txt").unwrap();
+
+        let mut t = Tournament::new(30);
+        t.add(Box::new(ConstantStrategy(State::Rock)));
+        t.add(Box::new(ConstantStrategy(State::Paper)));
+        t.add(Box::new(
+            CyclicStrategy::new(vec![State::Scissors, State::Rock]).unwrap(),
+        ));
+        t.add(Box::new(RandomStrategy::new(seed)));
+        t.add(Box::new(FrequencyStrategy::default()));
+        t.add(Box::new(
+            ReplayStrategy::from_guide("guide", &RuleSet::classic(), &guide, &ShapeDecoder)
+                .unwrap(),
+        ));
+        t.add(Box::new(RandomStrategy::new(seed + 1)));
+        t
+    }
+
+    #[test]
+    fn test_match() {
+        let mut t = Tournament::new(10);
+        t.add(Box::new(ConstantStrategy(State::Rock)));
+        t.add(Box::new(ConstantStrategy(State::Paper)));
+
+        let result = t.play_match(1, 0);
+        assert_eq!(result.rounds_won, (10, 0));
+        assert_eq!(result.scores, (80, 10));
+
+        let board = t.leaderboard();
+        assert_eq!(board.standings[0].name, "always-paper");
+        assert_eq!(board.standings[0].points, 3);
+        assert_eq!(board.standings[1].lost, 1);
+    }
+
+    #[test]
+    fn test_strategy_errors() {
+        assert_eq!(
+            CyclicStrategy::new(vec![]).err(),
+            Some(StrategyError::EmptySequence)
+        );
+
+        let rules = RuleSet::rpsls();
+        assert_eq!(
+            ReplayStrategy::from_guide("empty", &rules, &[], &ShapeDecoder).err(),
+            Some(StrategyError::EmptyGuide)
+        );
+
+        let guide = parse_strategy_guide_with(&rules, Cursor::new("A X\nB W\n")).unwrap();
+        assert!(ReplayStrategy::from_guide("rps", &rules, &guide[..1], &ShapeDecoder).is_ok());
+        assert_eq!(
+            ReplayStrategy::from_guide("rpsls", &rules, &guide, &ShapeDecoder).err(),
+            Some(StrategyError::UnplayableWeapon(String::from("Spock")))
+        );
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let board = roster(42).round_robin();
+        assert_eq!(board.standings.len(), 7);
+
+        // Everyone plays six matches
+        for s in board.standings.iter() {
+            assert_eq!(s.won + s.drawn + s.lost, 6);
+        }
+        assert_eq!(board, roster(42).round_robin());
+    }
+
+    #[test]
+    fn test_swiss() {
+        let board = roster(7).swiss(3, 7);
+        assert_eq!(board, roster(7).swiss(3, 7));
+
+        // Seven players: three matches a round and one bye
+        let matches: u32 = board
+            .standings
+            .iter()
+            .map(|s| s.won + s.drawn + s.lost)
+            .sum();
+        let byes: u32 = board.standings.iter().map(|s| s.byes).sum();
+        assert_eq!(matches, 2 * 3 * 3);
+        assert_eq!(byes, 3);
+        assert!(board.standings.iter().all(|s| s.byes <= 1));
+    }
+
+    #[test]
+    fn test_guide_report() {
+        let rules = RuleSet::classic();
+        let guide = parse_strategy_file("input_test.txt").unwrap();
+        let report = GuideReport::classic(&rules, &guide);
+
+        let pt1 = report.tally_pt1();
+        assert_eq!(pt1.total(), score_strategy_guide(&guide));
+        assert_eq!((pt1.shape_points, pt1.outcome_points), (6, 9));
+        assert_eq!((pt1.wins, pt1.draws, pt1.losses), (1, 1, 1));
+
+        let pt2 = report.tally_pt2();
+        assert_eq!(pt2.total(), score_strategy_guide_pt2(&guide));
+        assert_eq!((pt2.wins, pt2.draws, pt2.losses), (1, 1, 1));
+
+        // A Y: win -> draw, B X: lose -> lose, C Z: draw -> win
+        assert_eq!(report.changed_rounds(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_guide_report_render() {
+        let rules = RuleSet::classic();
+        let guide = parse_strategy_file("input_test.txt").unwrap();
+        let report = GuideReport::classic(&rules, &guide);
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "1,A,Y,Paper,win,2,6,Rock,draw,1,3,true");
+        assert_eq!(lines[2], "2,B,X,Rock,lose,1,0,Rock,lose,1,0,false");
+
+        let table = report.to_table();
+        assert!(table.contains("part 1: 15 (6 shape + 9 outcome), 1W 1D 1L"));
+        assert!(table.contains("2 of 3 rounds change outcome"));
+    }
 }
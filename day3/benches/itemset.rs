@@ -0,0 +1,157 @@
+//! Compare the `HashSet` intersections the solutions used to rely on with
+//! `ItemSet` bitmasks. Run with `cargo bench -p day3`; set `BENCH_LINES` to
+//! change the input size from the default of 10 million rucksacks.
+
+use std::collections::HashSet;
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use day3::find_shared_items;
+use day3::itemset::ItemSet;
+use day3::priority;
+
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Rucksacks packed back to back, with `offsets[i]..offsets[i + 1]` being
+/// line `i`. Each line has exactly one item in both halves, and every group
+/// of three lines shares exactly one badge.
+struct Input {
+    items: Vec<char>,
+    offsets: Vec<usize>,
+}
+
+impl Input {
+    fn line(&self, idx: usize) -> &[char] {
+        &self.items[self.offsets[idx]..self.offsets[idx + 1]]
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+}
+
+fn generate(num_lines: usize) -> Input {
+    let mut state: u64 = 0x5eed;
+    let mut next = |n: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % n as u64) as usize
+    };
+
+    let mut items = Vec::with_capacity(num_lines * 32);
+    let mut offsets = Vec::with_capacity(num_lines + 1);
+    offsets.push(0);
+
+    for _ in 0..num_lines / 3 {
+        // Split everything but the badge into three disjoint pools, one per
+        // member, so the badge is the only item common to the group.
+        let badge = ALPHABET[next(ALPHABET.len())] as char;
+        let mut pool: Vec<char> = ALPHABET
+            .iter()
+            .map(|&b| b as char)
+            .filter(|&c| c != badge)
+            .collect();
+        let shift = next(pool.len());
+        pool.rotate_left(shift);
+
+        for member in pool.chunks(17) {
+            // One item goes in both halves, the rest stay on one side
+            let shared = next(member.len());
+            let others: Vec<char> = (0..member.len())
+                .filter(|&i| i != shared)
+                .map(|i| member[i])
+                .collect();
+            let (left_only, right_only) = others.split_at(others.len() / 2);
+
+            let half = 8 + next(17);
+            for _ in 0..half - 2 {
+                items.push(left_only[next(left_only.len())]);
+            }
+            items.push(member[shared]);
+            items.push(badge);
+            for _ in 0..half - 1 {
+                items.push(right_only[next(right_only.len())]);
+            }
+            items.push(member[shared]);
+            offsets.push(items.len());
+        }
+    }
+
+    Input { items, offsets }
+}
+
+fn legacy_shared_item(left: &[char], right: &[char]) -> char {
+    let left_set: HashSet<&char> = left.iter().collect();
+    *right.iter().find(|item| left_set.contains(item)).unwrap()
+}
+
+fn time<F: FnMut() -> u64>(name: &str, mut f: F) -> Duration {
+    let start = Instant::now();
+    let result = black_box(f());
+    let elapsed = start.elapsed();
+    println!("{:<28} {:>10.3?}  (checksum {})", name, elapsed, result);
+    elapsed
+}
+
+fn main() {
+    let num_lines: usize = env::var("BENCH_LINES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10_000_000);
+    let num_lines = num_lines - num_lines % 3;
+
+    println!("Generating {} rucksacks...", num_lines);
+    let input = generate(num_lines);
+
+    let legacy = time("compartments: HashSet", || {
+        (0..input.len())
+            .map(|idx| {
+                let line = input.line(idx);
+                let (left, right) = line.split_at(line.len() / 2);
+                priority(legacy_shared_item(left, right)).unwrap() as u64
+            })
+            .sum()
+    });
+    let bits = time("compartments: ItemSet", || {
+        (0..input.len())
+            .map(|idx| {
+                let line = input.line(idx);
+                let (left, right) = line.split_at(line.len() / 2);
+                (ItemSet::from_items(left).unwrap() & ItemSet::from_items(right).unwrap())
+                    .priority_sum() as u64
+            })
+            .sum()
+    });
+    println!(
+        "speedup: {:.1}x\n",
+        legacy.as_secs_f64() / bits.as_secs_f64()
+    );
+
+    let legacy = time("badges: HashSet", || {
+        (0..input.len())
+            .step_by(3)
+            .map(|idx| {
+                let shared = find_shared_items(vec![
+                    input.line(idx),
+                    input.line(idx + 1),
+                    input.line(idx + 2),
+                ]);
+                priority(shared[0]).unwrap() as u64
+            })
+            .sum()
+    });
+    let bits = time("badges: ItemSet", || {
+        (0..input.len())
+            .step_by(3)
+            .map(|idx| {
+                let shared = ItemSet::from_items(input.line(idx)).unwrap()
+                    & ItemSet::from_items(input.line(idx + 1)).unwrap()
+                    & ItemSet::from_items(input.line(idx + 2)).unwrap();
+                shared.priority_sum() as u64
+            })
+            .sum()
+    });
+    println!("speedup: {:.1}x", legacy.as_secs_f64() / bits.as_secs_f64());
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    InvalidGroupSize,
+    /// A rucksack can't be split into two equal compartments.
+    OddLength {
+        line: usize,
+        len: usize,
+    },
+    /// An item the priority scheme doesn't know.
+    UnknownItem {
+        line: usize,
+        column: usize,
+        item: char,
+    },
+    /// A team starting at `line` ran out of rucksacks.
+    IncompleteTeam {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "I/O error: {}", e),
+            ParseError::InvalidGroupSize => write!(f, "group size must be at least 1"),
+            ParseError::OddLength { line, len } => {
+                write!(
+                    f,
+                    "line {}: rucksack has an odd number of items ({})",
+                    line, len
+                )
+            }
+            ParseError::UnknownItem { line, column, item } => {
+                write!(f, "{}:{}: unknown item {:?}", line, column, item)
+            }
+            ParseError::IncompleteTeam {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: team has {} rucksacks, expected {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use crate::itemset::Items;
+use crate::{Rucksack, Team};
+
+/// How many of one item type a rucksack holds in each compartment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Holding {
+    pub sack: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Holding {
+    pub fn total(&self) -> usize {
+        self.left + self.right
+    }
+}
+
+/// Index from each item type to the rucksacks carrying it.
+#[derive(Debug, Clone, Default)]
+pub struct Inventory {
+    items: BTreeMap<char, Vec<Holding>>,
+    /// The team of each rucksack, if built from teams.
+    teams: Vec<Option<usize>>,
+}
+
+impl Inventory {
+    pub fn new(sacks: &[Rucksack]) -> Self {
+        let mut inventory = Inventory::default();
+        for sack in sacks.iter() {
+            inventory.add(sack, None);
+        }
+        inventory
+    }
+
+    /// Index every member of every team, numbering rucksacks in order.
+    pub fn from_teams(teams: &[Team]) -> Self {
+        let mut inventory = Inventory::default();
+        for (team_idx, team) in teams.iter().enumerate() {
+            for member in team.members.iter() {
+                let sack = Rucksack::parse(&member.iter().collect::<String>());
+                inventory.add(&sack, Some(team_idx));
+            }
+        }
+        inventory
+    }
+
+    fn add(&mut self, sack: &Rucksack, team: Option<usize>) {
+        let idx = self.teams.len();
+        self.teams.push(team);
+
+        for item in sack.items().iter() {
+            let count = |side: String| side.chars().filter(|&c| c == item).count();
+            self.items.entry(item).or_default().push(Holding {
+                sack: idx,
+                left: count(sack.left_sack()),
+                right: count(sack.right_sack()),
+            });
+        }
+    }
+
+    pub fn num_sacks(&self) -> usize {
+        self.teams.len()
+    }
+
+    pub fn team_of(&self, sack: usize) -> Option<usize> {
+        self.teams.get(sack).copied().flatten()
+    }
+
+    /// Every item type seen in any rucksack.
+    pub fn item_types(&self) -> Items {
+        self.items.keys().copied().collect()
+    }
+
+    /// The rucksacks carrying `item`, in order.
+    pub fn carriers(&self, item: char) -> &[Holding] {
+        self.items.get(&item).map_or(&[], |h| h.as_slice())
+    }
+
+    /// Items found in every rucksack.
+    pub fn in_every_sack(&self) -> Items {
+        if self.num_sacks() == 0 {
+            return Items::new();
+        }
+        self.items
+            .iter()
+            .filter(|(_, h)| h.len() == self.num_sacks())
+            .map(|(&item, _)| item)
+            .collect()
+    }
+
+    /// Items found in exactly one rucksack, with that rucksack.
+    pub fn unique_items(&self) -> Vec<(char, usize)> {
+        self.items
+            .iter()
+            .filter(|(_, h)| h.len() == 1)
+            .map(|(&item, h)| (item, h[0].sack))
+            .collect()
+    }
+
+    /// The `k` items carried by the most rucksacks, with how many carry
+    /// them. Ties go to the item with more copies overall, then to the
+    /// smaller character.
+    pub fn most_common(&self, k: usize) -> Vec<(char, usize)> {
+        let mut counts: Vec<(char, usize, usize)> = self
+            .items
+            .iter()
+            .map(|(&item, h)| (item, h.len(), h.iter().map(|h| h.total()).sum()))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+        counts
+            .into_iter()
+            .take(k)
+            .map(|(item, sacks, _)| (item, sacks))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inventory_queries() {
+        let sacks: Vec<Rucksack> = ["abca", "bbcd", "cbxy"]
+            .iter()
+            .map(|s| Rucksack::parse(s))
+            .collect();
+        let inventory = Inventory::new(&sacks);
+
+        assert_eq!(inventory.num_sacks(), 3);
+        assert_eq!(
+            inventory.carriers('b'),
+            &[
+                Holding {
+                    sack: 0,
+                    left: 1,
+                    right: 0
+                },
+                Holding {
+                    sack: 1,
+                    left: 2,
+                    right: 0
+                },
+                Holding {
+                    sack: 2,
+                    left: 1,
+                    right: 0
+                },
+            ]
+        );
+        assert!(inventory.carriers('z').is_empty());
+        assert_eq!(inventory.in_every_sack().iter().collect::<String>(), "bc");
+        assert_eq!(
+            inventory.unique_items(),
+            vec![('a', 0), ('d', 1), ('x', 2), ('y', 2)]
+        );
+        assert_eq!(inventory.most_common(2), vec![('b', 3), ('c', 3)]);
+        assert_eq!(inventory.team_of(0), None);
+    }
+}
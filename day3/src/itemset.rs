@@ -0,0 +1,369 @@
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+
+use crate::priority;
+use crate::scheme::PriorityScheme;
+
+/// A set of letter items, one bit per item with bit `priority - 1` set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ItemSet(u64);
+
+fn letter_bit(item: char) -> Option<u32> {
+    priority(item).map(|p| p - 1)
+}
+
+fn item_for_bit(bit: u32) -> char {
+    if bit < 26 {
+        char::from(b'a' + bit as u8)
+    } else {
+        char::from(b'A' + (bit - 26) as u8)
+    }
+}
+
+impl ItemSet {
+    pub fn new() -> Self {
+        ItemSet(0)
+    }
+
+    /// The set holding every item type.
+    pub fn all() -> Self {
+        ItemSet((1 << 52) - 1)
+    }
+
+    /// The set of `items`, or `None` if one isn't in `[a-zA-Z]`.
+    pub fn from_items(items: &[char]) -> Option<Self> {
+        let mut set = ItemSet::new();
+        for &item in items {
+            if !set.insert(item) {
+                return None;
+            }
+        }
+        Some(set)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Add `item`, returning false and leaving the set alone if it isn't in
+    /// `[a-zA-Z]`.
+    pub fn insert(&mut self, item: char) -> bool {
+        match letter_bit(item) {
+            Some(bit) => {
+                self.0 |= 1 << bit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, item: char) -> bool {
+        letter_bit(item).is_some_and(|bit| self.0 & (1 << bit) != 0)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Items in priority order.
+    pub fn iter(&self) -> impl Iterator<Item = char> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let bit = bits.trailing_zeros();
+            bits &= bits - 1;
+            Some(item_for_bit(bit))
+        })
+    }
+
+    pub fn priority_sum(&self) -> u32 {
+        let mut bits = self.0;
+        let mut sum = 0;
+        while bits != 0 {
+            sum += bits.trailing_zeros() + 1;
+            bits &= bits - 1;
+        }
+        sum
+    }
+}
+
+impl BitAnd for ItemSet {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        ItemSet(self.0 & other.0)
+    }
+}
+
+impl BitOr for ItemSet {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        ItemSet(self.0 | other.0)
+    }
+}
+
+impl BitAndAssign for ItemSet {
+    fn bitand_assign(&mut self, other: Self) {
+        self.0 &= other.0;
+    }
+}
+
+impl BitOrAssign for ItemSet {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+/// A set of items from any alphabet, for schemes that go beyond letters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ExtendedItemSet(BTreeSet<char>);
+
+impl ExtendedItemSet {
+    pub fn new() -> Self {
+        ExtendedItemSet::default()
+    }
+
+    pub fn from_items(items: &[char]) -> Self {
+        items.iter().copied().collect()
+    }
+
+    pub fn insert(&mut self, item: char) {
+        self.0.insert(item);
+    }
+
+    pub fn contains(&self, item: char) -> bool {
+        self.0.contains(&item)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Items in character order.
+    pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl FromIterator<char> for ExtendedItemSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        ExtendedItemSet(iter.into_iter().collect())
+    }
+}
+
+impl From<ItemSet> for ExtendedItemSet {
+    fn from(set: ItemSet) -> Self {
+        set.iter().collect()
+    }
+}
+
+impl BitAnd for &ExtendedItemSet {
+    type Output = ExtendedItemSet;
+
+    fn bitand(self, other: Self) -> ExtendedItemSet {
+        ExtendedItemSet(self.0.intersection(&other.0).copied().collect())
+    }
+}
+
+impl BitOr for &ExtendedItemSet {
+    type Output = ExtendedItemSet;
+
+    fn bitor(self, other: Self) -> ExtendedItemSet {
+        ExtendedItemSet(self.0.union(&other.0).copied().collect())
+    }
+}
+
+/// The items of a rucksack or team: an `ItemSet` bitmask when they are all
+/// letters, an `ExtendedItemSet` otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Items {
+    Letters(ItemSet),
+    Extended(ExtendedItemSet),
+}
+
+impl Default for Items {
+    fn default() -> Self {
+        Items::Letters(ItemSet::new())
+    }
+}
+
+impl Items {
+    pub fn new() -> Self {
+        Items::default()
+    }
+
+    /// Pick the set type from the items themselves.
+    pub fn from_items(items: &[char]) -> Self {
+        match ItemSet::from_items(items) {
+            Some(set) => Items::Letters(set),
+            None => Items::Extended(ExtendedItemSet::from_items(items)),
+        }
+    }
+
+    /// Pick the set type `scheme` asks for, falling back to an
+    /// `ExtendedItemSet` for items that aren't letters.
+    pub fn from_items_with(items: &[char], scheme: &dyn PriorityScheme) -> Self {
+        match ItemSet::from_items(items).filter(|_| scheme.letters_only()) {
+            Some(set) => Items::Letters(set),
+            None => Items::Extended(ExtendedItemSet::from_items(items)),
+        }
+    }
+
+    fn extended(&self) -> ExtendedItemSet {
+        match self {
+            Items::Letters(set) => ExtendedItemSet::from(*set),
+            Items::Extended(set) => set.clone(),
+        }
+    }
+
+    pub fn contains(&self, item: char) -> bool {
+        match self {
+            Items::Letters(set) => set.contains(item),
+            Items::Extended(set) => set.contains(item),
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        match self {
+            Items::Letters(set) => set.len(),
+            Items::Extended(set) => set.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Letters in priority order, anything else in character order.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = char> + '_> {
+        match self {
+            Items::Letters(set) => Box::new(set.iter()),
+            Items::Extended(set) => Box::new(set.iter()),
+        }
+    }
+
+    /// Sum of the priorities under `scheme`, or `None` if it doesn't know
+    /// one of the items.
+    pub fn priority_sum_with(&self, scheme: &dyn PriorityScheme) -> Option<u32> {
+        self.iter().map(|item| scheme.priority(item)).sum()
+    }
+}
+
+impl FromIterator<char> for Items {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Items::from_items(&iter.into_iter().collect::<Vec<char>>())
+    }
+}
+
+impl BitAnd for &Items {
+    type Output = Items;
+
+    fn bitand(self, other: Self) -> Items {
+        match (self, other) {
+            (Items::Letters(a), Items::Letters(b)) => Items::Letters(*a & *b),
+            _ => Items::Extended(&self.extended() & &other.extended()),
+        }
+    }
+}
+
+impl BitOr for &Items {
+    type Output = Items;
+
+    fn bitor(self, other: Self) -> Items {
+        match (self, other) {
+            (Items::Letters(a), Items::Letters(b)) => Items::Letters(*a | *b),
+            _ => Items::Extended(&self.extended() | &other.extended()),
+        }
+    }
+}
+
+impl BitAnd for Items {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        &self & &other
+    }
+}
+
+impl BitOr for Items {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        &self | &other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::{LetterScheme, TableScheme};
+
+    #[test]
+    fn test_itemset_ops() {
+        let a = ItemSet::from_items(&['a', 'b', 'Z']).unwrap();
+        let b = ItemSet::from_items(&['b', 'c', 'Z']).unwrap();
+
+        assert_eq!((a & b).iter().collect::<String>(), "bZ");
+        assert_eq!((a | b).len(), 4);
+        assert!(a.contains('Z'));
+        assert!(!a.contains('c'));
+        assert!(!a.contains('é'));
+        assert_eq!(ItemSet::all().len(), 52);
+        assert_eq!((a & b).priority_sum(), 2 + 52);
+
+        assert_eq!(ItemSet::from_items(&['a', '1']), None);
+        let mut c = a;
+        assert!(!c.insert('é'));
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    fn test_items_unicode() {
+        let a: Items = "aé🍎".chars().collect();
+        let b: Items = "é🍎ü".chars().collect();
+        let letters: Items = "ab".chars().collect();
+        assert!(matches!(a, Items::Extended(_)));
+        assert!(matches!(letters, Items::Letters(_)));
+
+        let shared = &a & &b;
+        assert_eq!(shared.iter().collect::<String>(), "é🍎");
+        assert!(shared.contains('🍎'));
+        assert!(!shared.contains('a'));
+        assert_eq!((&a | &b).len(), 4);
+        assert_eq!((a & letters).iter().collect::<String>(), "a");
+    }
+
+    #[test]
+    fn test_items_by_scheme() {
+        let items = ['a', 'b'];
+        let table = TableScheme::new(&[('a', 1), ('b', 2), ('é', 3)]);
+        assert!(matches!(
+            Items::from_items_with(&items, &LetterScheme),
+            Items::Letters(_)
+        ));
+        assert!(matches!(
+            Items::from_items_with(&items, &table),
+            Items::Extended(_)
+        ));
+        assert!(matches!(
+            Items::from_items_with(&items, &TableScheme::new(&[('a', 1)])),
+            Items::Letters(_)
+        ));
+        assert!(matches!(
+            Items::from_items_with(&['a', '1'], &LetterScheme),
+            Items::Extended(_)
+        ));
+    }
+}
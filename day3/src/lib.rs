@@ -3,6 +3,17 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::primitive::char;
 
+pub mod error;
+pub mod inventory;
+pub mod itemset;
+pub mod partition;
+pub mod repack;
+pub mod scheme;
+
+use error::ParseError;
+use itemset::Items;
+use scheme::{LetterScheme, PriorityScheme};
+
 pub fn find_shared_items(chunks: Vec<&[char]>) -> Vec<char> {
     if chunks.is_empty() {
         return vec![];
@@ -26,19 +37,52 @@ pub fn find_shared_items(chunks: Vec<&[char]>) -> Vec<char> {
 pub struct Rucksack {
     left: Vec<char>,
     right: Vec<char>,
+    left_items: Items,
+    right_items: Items,
 }
 
 impl Rucksack {
+    /// Split `leftright` into two compartments holding the same number of
+    /// items.
     pub fn parse(leftright: &str) -> Rucksack {
-        let len = leftright.len();
-        if len % 2 != 0 {
+        Rucksack::parse_as(leftright, Items::from_items)
+    }
+
+    /// As `parse`, with the set type chosen by `scheme`. Items `scheme`
+    /// doesn't know and odd lengths are errors.
+    pub fn parse_with(
+        leftright: &str,
+        scheme: &dyn PriorityScheme,
+    ) -> Result<Rucksack, ParseError> {
+        Rucksack::parse_line(1, leftright, scheme)
+    }
+
+    /// `parse_with` for line `line_num` of a file.
+    fn parse_line(
+        line_num: usize,
+        line: &str,
+        scheme: &dyn PriorityScheme,
+    ) -> Result<Rucksack, ParseError> {
+        check_items(line_num, line, scheme)?;
+        check_length(line_num, line)?;
+        Ok(Rucksack::parse_as(line, |items| {
+            Items::from_items_with(items, scheme)
+        }))
+    }
+
+    fn parse_as<F: Fn(&[char]) -> Items>(leftright: &str, to_set: F) -> Rucksack {
+        let items: Vec<char> = leftright.chars().collect();
+        if !items.len().is_multiple_of(2) {
             panic!("leftright must be even length");
         }
 
-        let half = len / 2;
+        let (left, right) = items.split_at(items.len() / 2);
+        let (left, right) = (left.to_vec(), right.to_vec());
         Rucksack {
-            left: leftright[0..half].chars().collect(),
-            right: leftright[half..len].chars().collect(),
+            left_items: to_set(&left),
+            right_items: to_set(&right),
+            left,
+            right,
         }
     }
 
@@ -50,20 +94,38 @@ impl Rucksack {
         self.right.iter().collect()
     }
 
-    pub fn find_shared_item(&self) -> char {
-        let mut left_set = HashSet::new();
-        for item in self.left.iter() {
-            left_set.insert(item);
-        }
+    pub fn left_items(&self) -> &Items {
+        &self.left_items
+    }
 
-        // Now query right against left
-        for item in self.right.iter() {
-            if left_set.contains(&item) {
-                return *item;
-            }
-        }
+    pub fn right_items(&self) -> &Items {
+        &self.right_items
+    }
+
+    /// Every item in the rucksack, left compartment first.
+    pub fn contents(&self) -> Vec<char> {
+        self.left.iter().chain(self.right.iter()).copied().collect()
+    }
+
+    /// Every item type carried, in either compartment.
+    pub fn items(&self) -> Items {
+        &self.left_items | &self.right_items
+    }
 
-        panic!("No shared item found in rucksack.");
+    /// Every item type found in both compartments.
+    pub fn shared_items(&self) -> Items {
+        &self.left_items & &self.right_items
+    }
+
+    /// The one item found in both compartments, or `None` if they share no
+    /// item or more than one.
+    pub fn find_shared_item(&self) -> Option<char> {
+        let shared = self.shared_items();
+        let mut items = shared.iter();
+        match (items.next(), items.next()) {
+            (Some(item), None) => Some(item),
+            _ => None,
+        }
     }
 }
 
@@ -77,79 +139,275 @@ pub fn parse_rucksacks(path: &str) -> Vec<Rucksack> {
     sacks
 }
 
+/// Check that `line` holds only items `scheme` knows about.
+fn check_items(line_num: usize, line: &str, scheme: &dyn PriorityScheme) -> Result<(), ParseError> {
+    match line.chars().enumerate().find(|&(_, c)| !scheme.contains(c)) {
+        Some((col, item)) => Err(ParseError::UnknownItem {
+            line: line_num,
+            column: col + 1,
+            item,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn check_length(line_num: usize, line: &str) -> Result<(), ParseError> {
+    let len = line.chars().count();
+    if !len.is_multiple_of(2) {
+        return Err(ParseError::OddLength {
+            line: line_num,
+            len,
+        });
+    }
+    Ok(())
+}
+
+/// Read one rucksack per line, skipping blank lines.
+pub fn read_rucksacks<R: BufRead>(
+    reader: R,
+    scheme: &dyn PriorityScheme,
+) -> Result<Vec<Rucksack>, ParseError> {
+    let mut sacks = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        sacks.push(Rucksack::parse_line(idx + 1, line, scheme)?);
+    }
+    Ok(sacks)
+}
+
+pub fn parse_rucksacks_with(
+    path: &str,
+    scheme: &dyn PriorityScheme,
+) -> Result<Vec<Rucksack>, ParseError> {
+    read_rucksacks(BufReader::new(File::open(path)?), scheme)
+}
+
+#[derive(Debug)]
 pub struct Team {
-    pub group_a: Vec<char>,
-    pub group_b: Vec<char>,
-    pub group_c: Vec<char>,
+    pub members: Vec<Vec<char>>,
 }
 
 impl Team {
-    pub fn parse(a: &str, b: &str, c: &str) -> Team {
+    pub fn parse<S: AsRef<str>>(members: &[S]) -> Team {
         Team {
-            group_a: a.chars().collect(),
-            group_b: b.chars().collect(),
-            group_c: c.chars().collect(),
+            members: members
+                .iter()
+                .map(|m| m.as_ref().chars().collect())
+                .collect(),
         }
     }
 
-    pub fn badge(&self) -> char {
-        let shared = find_shared_items(vec![&self.group_a, &self.group_b, &self.group_c]);
-        if shared.len() != 1 {
-            panic!("Found more than 1 item in all groups {:?}", shared);
-        }
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
 
-        shared[0]
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Items carried by every member of the team.
+    pub fn shared_items(&self) -> Items {
+        self.members
+            .iter()
+            .map(|m| Items::from_items(m))
+            .reduce(|a, b| a & b)
+            .unwrap_or_default()
+    }
+
+    /// The one item every member carries, or `None` if they share no item
+    /// or more than one.
+    pub fn badge(&self) -> Option<char> {
+        let shared = self.shared_items();
+        let mut items = shared.iter();
+        match (items.next(), items.next()) {
+            (Some(badge), None) => Some(badge),
+            _ => None,
+        }
     }
 }
 
-pub fn parse_teams(path: &str) -> Vec<Team> {
-    let reader = BufReader::new(File::open(path).expect("File not found"));
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TeamLayout {
+    /// Consecutive runs of this many rucksacks.
+    Fixed(usize),
+    /// Teams of any size, separated by blank lines.
+    BlankSeparated,
+}
 
-    let lines: Vec<String> = reader.lines().map(|s| s.unwrap()).collect();
+pub fn read_teams<R: BufRead>(reader: R, layout: TeamLayout) -> Result<Vec<Team>, ParseError> {
+    read_teams_with(reader, layout, &LetterScheme)
+}
 
-    if lines.len() % 3 != 0 {
-        panic!("File length must be divisible by 3");
+pub fn read_teams_with<R: BufRead>(
+    reader: R,
+    layout: TeamLayout,
+    scheme: &dyn PriorityScheme,
+) -> Result<Vec<Team>, ParseError> {
+    if layout == TeamLayout::Fixed(0) {
+        return Err(ParseError::InvalidGroupSize);
     }
 
     let mut teams: Vec<Team> = Vec::new();
-    for idx in (0..lines.len()).step_by(3) {
-        teams.push(Team::parse(&lines[idx], &lines[idx + 1], &lines[idx + 2]));
+    let mut members: Vec<String> = Vec::new();
+    let mut first_line = 0;
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() {
+            if layout == TeamLayout::BlankSeparated && !members.is_empty() {
+                teams.push(Team::parse(&members));
+                members.clear();
+            }
+            continue;
+        }
+
+        check_items(idx + 1, line, scheme)?;
+        check_length(idx + 1, line)?;
+        if members.is_empty() {
+            first_line = idx + 1;
+        }
+        members.push(String::from(line));
+
+        if layout == TeamLayout::Fixed(members.len()) {
+            teams.push(Team::parse(&members));
+            members.clear();
+        }
     }
-    teams
-}
 
-fn priority(token: char) -> u32 {
-    if !token.is_ascii_alphabetic() {
-        panic!("char must be [a-zA-Z]");
+    if !members.is_empty() {
+        match layout {
+            TeamLayout::Fixed(size) => {
+                return Err(ParseError::IncompleteTeam {
+                    line: first_line,
+                    expected: size,
+                    found: members.len(),
+                })
+            }
+            TeamLayout::BlankSeparated => teams.push(Team::parse(&members)),
+        }
     }
 
-    let (base_char, offset): (char, u32) = if token.is_lowercase() {
-        ('a', 1)
-    } else {
-        ('A', 27)
-    };
+    Ok(teams)
+}
+
+pub fn parse_teams_with(path: &str, size: usize) -> Result<Vec<Team>, ParseError> {
+    read_teams(BufReader::new(File::open(path)?), TeamLayout::Fixed(size))
+}
+
+pub fn parse_teams_with_scheme(
+    path: &str,
+    size: usize,
+    scheme: &dyn PriorityScheme,
+) -> Result<Vec<Team>, ParseError> {
+    read_teams_with(
+        BufReader::new(File::open(path)?),
+        TeamLayout::Fixed(size),
+        scheme,
+    )
+}
+
+pub fn parse_teams_blank_separated(path: &str) -> Result<Vec<Team>, ParseError> {
+    read_teams(
+        BufReader::new(File::open(path)?),
+        TeamLayout::BlankSeparated,
+    )
+}
+
+pub fn parse_teams(path: &str) -> Result<Vec<Team>, ParseError> {
+    parse_teams_with(path, 3)
+}
+
+/// Priority under the puzzle's `LetterScheme`, or `None` for anything
+/// outside `[a-zA-Z]`.
+pub fn priority(token: char) -> Option<u32> {
+    LetterScheme.priority(token)
+}
+
+/// `overlap_priority_with` under the puzzle's `LetterScheme`.
+pub fn overlap_priority(sacks: &[Rucksack]) -> Option<u32> {
+    overlap_priority_with(sacks, &LetterScheme)
+}
 
-    offset + u32::from(token) - u32::from(base_char)
+/// `badge_priorities_with` under the puzzle's `LetterScheme`.
+pub fn badge_priorities(teams: &[Team]) -> Option<u32> {
+    badge_priorities_with(teams, &LetterScheme)
 }
 
-pub fn overlap_priority(sacks: &[Rucksack]) -> u32 {
-    sacks.iter().map(|s| priority(s.find_shared_item())).sum()
+/// Sum of the priorities of every item shared between compartments, or
+/// `None` if `scheme` doesn't know one of them.
+pub fn overlap_priority_with(sacks: &[Rucksack], scheme: &dyn PriorityScheme) -> Option<u32> {
+    sacks
+        .iter()
+        .map(|s| s.shared_items().priority_sum_with(scheme))
+        .sum()
 }
 
-pub fn badge_priorities(teams: &[Team]) -> u32 {
-    teams.iter().map(|t| priority(t.badge())).sum()
+/// Sum of the badge priorities under `scheme`, or `None` if a team has no
+/// single badge or `scheme` doesn't know it.
+pub fn badge_priorities_with(teams: &[Team], scheme: &dyn PriorityScheme) -> Option<u32> {
+    teams
+        .iter()
+        .map(|t| t.badge().and_then(|badge| scheme.priority(badge)))
+        .sum()
 }
 
 #[cfg(test)]
 mod tests_day3 {
     use super::*;
+    use scheme::TableScheme;
 
     #[test]
     fn test_priority() {
-        assert_eq!(priority('a'), 1);
-        assert_eq!(priority('b'), 2);
-        assert_eq!(priority('z'), 26);
-        assert_eq!(priority('A'), 27);
-        assert_eq!(priority('Z'), 52);
+        assert_eq!(priority('a'), Some(1));
+        assert_eq!(priority('b'), Some(2));
+        assert_eq!(priority('z'), Some(26));
+        assert_eq!(priority('A'), Some(27));
+        assert_eq!(priority('Z'), Some(52));
+        assert_eq!(priority('é'), None);
+        assert_eq!(overlap_priority(&[Rucksack::parse("aééb")]), None);
+    }
+
+    #[test]
+    fn test_badge_priorities_agree() {
+        let shared = [Team::parse(&["abc", "cab", "bca"])];
+        assert_eq!(badge_priorities(&shared), None);
+        assert_eq!(badge_priorities_with(&shared, &LetterScheme), None);
+
+        let single = [Team::parse(&["ab", "bc", "bd"])];
+        assert_eq!(badge_priorities(&single), Some(2));
+        assert_eq!(badge_priorities_with(&single, &LetterScheme), Some(2));
+        let table = TableScheme::new(&[('b', 7)]);
+        assert_eq!(badge_priorities_with(&single, &table), Some(7));
+    }
+
+    #[test]
+    fn test_parse_with_errors() {
+        assert!(matches!(
+            Rucksack::parse_with("a1a1", &LetterScheme),
+            Err(ParseError::UnknownItem {
+                line: 1,
+                column: 2,
+                item: '1'
+            })
+        ));
+        assert!(matches!(
+            Rucksack::parse_with("abc", &LetterScheme),
+            Err(ParseError::OddLength { line: 1, len: 3 })
+        ));
+
+        let table = TableScheme::new(&[('a', 1), ('b', 2)]);
+        assert!(matches!(
+            Rucksack::parse_with("abé", &table),
+            Err(ParseError::UnknownItem { item: 'é', .. })
+        ));
+        let sack = Rucksack::parse_with("abab", &table).unwrap();
+        assert_eq!(sack.shared_items().priority_sum_with(&table), Some(3));
     }
 }
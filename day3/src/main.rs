@@ -1,9 +1,145 @@
+use day3::inventory::Inventory;
+use day3::repack::repack_all;
+use day3::scheme::{LetterScheme, PriorityScheme, TableScheme};
 use day3::*;
 
+use std::env;
+use std::process;
+
+fn usage() -> ! {
+    eprintln!("Usage: day3 [PATH]");
+    eprintln!("       day3 repack [PATH]");
+    eprintln!("       day3 query [--input PATH] [--scheme PATH] [--teams N] QUERY");
+    eprintln!();
+    eprintln!("Queries: carriers ITEM | common | unique | top K");
+    process::exit(2);
+}
+
+fn parse_sacks(path: &str) -> Vec<Rucksack> {
+    parse_rucksacks_with(path, &LetterScheme).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    })
+}
+
+fn repack(mut args: impl Iterator<Item = String>) {
+    let path = match args.next() {
+        Some(arg) if arg.starts_with('-') => usage(),
+        arg => arg.unwrap_or_else(|| String::from("day3/input.txt")),
+    };
+
+    let summary = repack_all(&parse_sacks(&path));
+    println!("Moves: {}", summary.moves);
+    println!("Cost: {}", summary.cost);
+    if !summary.impossible.is_empty() {
+        let lines: Vec<String> = summary
+            .impossible
+            .iter()
+            .map(|idx| (idx + 1).to_string())
+            .collect();
+        println!("Impossible: lines {}", lines.join(", "));
+    }
+}
+
+fn query(mut args: impl Iterator<Item = String>) {
+    let mut path = String::from("day3/input.txt");
+    let mut scheme: Box<dyn PriorityScheme> = Box::new(LetterScheme);
+    let mut team_size: Option<usize> = None;
+    let mut query: Vec<String> = vec![];
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--input" => path = value(),
+            "--scheme" => {
+                let scheme_path = value();
+                scheme = Box::new(TableScheme::from_path(&scheme_path).unwrap_or_else(|e| {
+                    eprintln!("{}: {}", scheme_path, e);
+                    process::exit(1);
+                }))
+            }
+            "--teams" => team_size = Some(value().parse().unwrap_or_else(|_| usage())),
+            _ if arg.starts_with('-') => usage(),
+            _ => query.push(arg),
+        }
+    }
+
+    let inventory = match team_size {
+        Some(size) => parse_teams_with_scheme(&path, size, scheme.as_ref())
+            .map(|teams| Inventory::from_teams(&teams)),
+        None => parse_rucksacks_with(&path, scheme.as_ref()).map(|sacks| Inventory::new(&sacks)),
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    });
+
+    let query: Vec<&str> = query.iter().map(|q| q.as_str()).collect();
+    match query.as_slice() {
+        ["carriers", item] => {
+            let mut chars = item.chars();
+            let item = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => usage(),
+            };
+            for h in inventory.carriers(item) {
+                let team = match inventory.team_of(h.sack) {
+                    Some(t) => format!(" (team {})", t + 1),
+                    None => String::new(),
+                };
+                println!(
+                    "sack {}{}: left {}, right {}",
+                    h.sack + 1,
+                    team,
+                    h.left,
+                    h.right
+                );
+            }
+        }
+        ["common"] => println!("{}", inventory.in_every_sack().iter().collect::<String>()),
+        ["unique"] => {
+            for (item, sack) in inventory.unique_items() {
+                println!("{} sack {}", item, sack + 1);
+            }
+        }
+        ["top", k] => {
+            let k = k.parse().unwrap_or_else(|_| usage());
+            for (item, sacks) in inventory.most_common(k) {
+                println!("{} {}", item, sacks);
+            }
+        }
+        _ => usage(),
+    }
+}
+
 fn main() {
-    let sacks = parse_rucksacks("day3/input.txt");
-    println!("Part 1: {}", overlap_priority(&sacks));
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("repack") => repack(args),
+        Some("query") => query(args),
+        Some(arg) if arg.starts_with('-') => usage(),
+        path => {
+            let path = path.unwrap_or("day3/input.txt");
+            let sacks = parse_sacks(path);
+            match overlap_priority(&sacks) {
+                Some(total) => println!("Part 1: {}", total),
+                None => {
+                    eprintln!("{}: a shared item has no priority", path);
+                    process::exit(1);
+                }
+            }
 
-    let teams = parse_teams("day3/input.txt");
-    println!("Part 2: {}", badge_priorities(&teams));
+            let teams = parse_teams(path).unwrap_or_else(|e| {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            });
+            match badge_priorities(&teams) {
+                Some(total) => println!("Part 2: {}", total),
+                None => {
+                    eprintln!("{}: a team does not share exactly one badge", path);
+                    process::exit(1);
+                }
+            }
+        }
+    }
 }
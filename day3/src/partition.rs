@@ -0,0 +1,231 @@
+use crate::itemset::Items;
+use crate::scheme::{LetterScheme, PriorityScheme};
+use crate::{find_shared_items, Rucksack};
+
+/// Three rucksacks whose only common item is `badge`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triple {
+    pub sacks: [usize; 3],
+    pub badge: char,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition {
+    /// Teams in order of their first rucksack.
+    pub teams: Vec<Triple>,
+    /// Rucksacks left out of the best partition found.
+    pub unplaced: Vec<usize>,
+    /// Whether no other partition places as many rucksacks.
+    pub unique: bool,
+}
+
+impl Partition {
+    pub fn is_complete(&self) -> bool {
+        self.unplaced.is_empty()
+    }
+
+    /// Badge priorities under the puzzle's `LetterScheme`.
+    pub fn badge_priorities(&self) -> Option<u32> {
+        self.badge_priorities_with(&LetterScheme)
+    }
+
+    /// Badge priorities under `scheme`, or `None` if it doesn't know one of
+    /// the badges.
+    pub fn badge_priorities_with(&self, scheme: &dyn PriorityScheme) -> Option<u32> {
+        self.teams.iter().map(|t| scheme.priority(t.badge)).sum()
+    }
+}
+
+/// Every triple of rucksacks sharing exactly one item, in lexicographic
+/// order of their indices.
+pub fn candidate_triples(sacks: &[Rucksack]) -> Vec<[usize; 3]> {
+    let items: Vec<Items> = sacks.iter().map(|s| s.items()).collect();
+    let mut triples = Vec::new();
+
+    for a in 0..items.len() {
+        for b in (a + 1)..items.len() {
+            let pair = &items[a] & &items[b];
+            if pair.is_empty() {
+                continue;
+            }
+            for (c, third) in items.iter().enumerate().skip(b + 1) {
+                if (&pair & third).len() == 1 {
+                    triples.push([a, b, c]);
+                }
+            }
+        }
+    }
+    triples
+}
+
+struct Search<'a> {
+    triples: &'a [[usize; 3]],
+    /// Candidate triples each rucksack belongs to.
+    options: Vec<Vec<usize>>,
+    /// Only partitions placing at least this many rucksacks are accepted.
+    target: usize,
+    used: Vec<bool>,
+    skipped: Vec<bool>,
+    chosen: Vec<usize>,
+    found: Option<Vec<usize>>,
+    /// Partitions found so far, capped at two.
+    num_found: usize,
+}
+
+impl Search<'_> {
+    fn is_open(&self, sack: usize) -> bool {
+        !self.used[sack] && !self.skipped[sack]
+    }
+
+    fn is_live(&self, triple: usize) -> bool {
+        self.triples[triple].iter().all(|&s| self.is_open(s))
+    }
+
+    fn run(&mut self) {
+        if self.num_found >= 2 {
+            return;
+        }
+
+        // Pick the open rucksack with the fewest ways left to place it,
+        // counting how many open rucksacks can still be placed at all
+        let mut branch: Option<(usize, usize)> = None;
+        let mut placeable = 0;
+        for sack in (0..self.used.len()).filter(|&s| self.is_open(s)) {
+            let live = self.options[sack]
+                .iter()
+                .filter(|&&t| self.is_live(t))
+                .count();
+            if live > 0 {
+                placeable += 1;
+            }
+            if branch.is_none_or(|(_, fewest)| live < fewest) {
+                branch = Some((sack, live));
+            }
+        }
+
+        if 3 * self.chosen.len() + placeable - placeable % 3 < self.target {
+            return;
+        }
+
+        let Some((sack, live)) = branch else {
+            self.num_found += 1;
+            if self.found.is_none() {
+                self.found = Some(self.chosen.clone());
+            }
+            return;
+        };
+
+        if live > 0 {
+            for idx in 0..self.options[sack].len() {
+                let triple = self.options[sack][idx];
+                if !self.is_live(triple) {
+                    continue;
+                }
+                for &s in self.triples[triple].iter() {
+                    self.used[s] = true;
+                }
+                self.chosen.push(triple);
+                self.run();
+                self.chosen.pop();
+                for &s in self.triples[triple].iter() {
+                    self.used[s] = false;
+                }
+            }
+        }
+
+        self.skipped[sack] = true;
+        self.run();
+        self.skipped[sack] = false;
+    }
+}
+
+/// Group rucksacks into teams of three that share exactly one item, placing
+/// as many rucksacks as possible.
+///
+/// This is an exact search for a partition placing a target number of
+/// rucksacks: it branches on the rucksack with the fewest remaining
+/// placements and prunes any branch that can no longer reach the target. The
+/// target starts at every rucksack that fits some triple and drops by one
+/// team each time no partition reaches it.
+pub fn partition_rucksacks(sacks: &[Rucksack]) -> Partition {
+    let triples = candidate_triples(sacks);
+    let mut options = vec![Vec::new(); sacks.len()];
+    for (idx, triple) in triples.iter().enumerate() {
+        for &s in triple.iter() {
+            options[s].push(idx);
+        }
+    }
+
+    let placeable = options.iter().filter(|o| !o.is_empty()).count();
+    let mut target = placeable - placeable % 3;
+    let search = loop {
+        let mut search = Search {
+            triples: &triples,
+            options: options.clone(),
+            target,
+            used: vec![false; sacks.len()],
+            skipped: vec![false; sacks.len()],
+            chosen: Vec::new(),
+            found: None,
+            num_found: 0,
+        };
+        search.run();
+        if search.found.is_some() || target == 0 {
+            break search;
+        }
+        target -= 3;
+    };
+
+    let mut placed = vec![false; sacks.len()];
+    let mut teams: Vec<Triple> = search
+        .found
+        .unwrap_or_default()
+        .iter()
+        .map(|&t| {
+            let sacks_idx = triples[t];
+            let contents: Vec<Vec<char>> = sacks_idx.iter().map(|&s| sacks[s].contents()).collect();
+            let shared = find_shared_items(contents.iter().map(|c| c.as_slice()).collect());
+            for &s in sacks_idx.iter() {
+                placed[s] = true;
+            }
+            Triple {
+                sacks: sacks_idx,
+                badge: shared[0],
+            }
+        })
+        .collect();
+    teams.sort_by_key(|t| t.sacks);
+
+    Partition {
+        teams,
+        unplaced: (0..sacks.len()).filter(|&s| !placed[s]).collect(),
+        unique: search.num_found == 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sacks(lines: &[&str]) -> Vec<Rucksack> {
+        lines.iter().map(|l| Rucksack::parse(l)).collect()
+    }
+
+    #[test]
+    fn test_unique_partition() {
+        // Only "a" ties the first three together and only "b" the last three
+        let p = partition_rucksacks(&sacks(&["axcy", "ayez", "azfx", "bgbh", "bhbi", "bibg"]));
+        assert!(p.is_complete());
+        assert!(p.unique);
+        assert_eq!(p.teams[0].sacks, [0, 1, 2]);
+        assert_eq!(p.teams[1].badge, 'b');
+    }
+
+    #[test]
+    fn test_unplaced() {
+        let p = partition_rucksacks(&sacks(&["abab", "acac", "adad", "KK"]));
+        assert_eq!(p.teams.len(), 1);
+        assert_eq!(p.unplaced, vec![3]);
+        assert!(p.unique);
+    }
+}
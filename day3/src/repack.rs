@@ -0,0 +1,246 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::scheme::{LetterScheme, PriorityScheme};
+use crate::Rucksack;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RepackError {
+    /// No choice of item types fills exactly half the rucksack.
+    Unbalanced {
+        size: usize,
+    },
+    UnknownItem(char),
+}
+
+impl fmt::Display for RepackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepackError::Unbalanced { size } => write!(
+                f,
+                "no split of item types fills a compartment of {} items",
+                size
+            ),
+            RepackError::UnknownItem(item) => write!(f, "unknown item {:?}", item),
+        }
+    }
+}
+
+impl Error for RepackError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemMove {
+    pub item: char,
+    pub from: Side,
+    /// Position of the item in its original compartment.
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepackPlan {
+    pub moves: Vec<ItemMove>,
+    cost: u32,
+}
+
+impl RepackPlan {
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Sum of the priorities of every item moved.
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// The rucksack after every move has been carried out.
+    pub fn apply(&self, sack: &Rucksack) -> Rucksack {
+        let moved = |side: Side, position: usize| {
+            self.moves
+                .iter()
+                .any(|m| m.from == side && m.position == position)
+        };
+        let left = sack.left_sack();
+        let right = sack.right_sack();
+
+        let mut new_left: String = left
+            .chars()
+            .enumerate()
+            .filter(|&(pos, _)| !moved(Side::Left, pos))
+            .map(|(_, item)| item)
+            .collect();
+        let mut new_right: String = right
+            .chars()
+            .enumerate()
+            .filter(|&(pos, _)| !moved(Side::Right, pos))
+            .map(|(_, item)| item)
+            .collect();
+        for m in self.moves.iter() {
+            match m.from {
+                Side::Left => new_right.push(m.item),
+                Side::Right => new_left.push(m.item),
+            }
+        }
+
+        Rucksack::parse(&(new_left + &new_right))
+    }
+}
+
+/// Fewest item moves, then lowest priority cost, that leave no item type in
+/// both compartments while keeping them the same size.
+///
+/// Every type ends up entirely on one side, so this picks the set of types
+/// to keep on the left whose counts add up to half the rucksack. A knapsack
+/// over the item types finds it in O(types * size).
+pub fn plan_repack(sack: &Rucksack) -> Result<RepackPlan, RepackError> {
+    plan_repack_with(sack, &LetterScheme)
+}
+
+/// As `plan_repack`, with item priorities from `scheme`.
+pub fn plan_repack_with(
+    sack: &Rucksack,
+    scheme: &dyn PriorityScheme,
+) -> Result<RepackPlan, RepackError> {
+    let left: Vec<char> = sack.left_sack().chars().collect();
+    let right: Vec<char> = sack.right_sack().chars().collect();
+    let half = left.len();
+
+    let types: Vec<(char, u32, usize, usize)> = sack
+        .items()
+        .iter()
+        .map(|t| {
+            let p = scheme.priority(t).ok_or(RepackError::UnknownItem(t))?;
+            let l = left.iter().filter(|&&c| c == t).count();
+            let r = right.iter().filter(|&&c| c == t).count();
+            Ok((t, p, l, r))
+        })
+        .collect::<Result<_, _>>()?;
+
+    // best[i][s]: cheapest (moves, cost) placing the first i types with s
+    // items on the left, and whether type i - 1 went left to get there
+    let mut best: Vec<Vec<Option<(usize, u32, bool)>>> =
+        vec![vec![None; half + 1]; types.len() + 1];
+    best[0][0] = Some((0, 0, false));
+    for (i, &(_, p, l, r)) in types.iter().enumerate() {
+        for s in 0..=half {
+            let Some((moves, cost, _)) = best[i][s] else {
+                continue;
+            };
+            let mut relax = |s: usize, moved: usize, to_left: bool| {
+                let candidate = (moves + moved, cost + moved as u32 * p, to_left);
+                if best[i + 1][s].is_none_or(|b| (candidate.0, candidate.1) < (b.0, b.1)) {
+                    best[i + 1][s] = Some(candidate);
+                }
+            };
+            relax(s, l, false);
+            if s + l + r <= half {
+                relax(s + l + r, r, true);
+            }
+        }
+    }
+
+    let Some((_, cost, _)) = best[types.len()][half] else {
+        return Err(RepackError::Unbalanced { size: half });
+    };
+
+    let mut moves = Vec::new();
+    let mut s = half;
+    for (i, &(item, _, l, r)) in types.iter().enumerate().rev() {
+        let (_, _, to_left) = best[i + 1][s].unwrap();
+        let (from, compartment) = if to_left {
+            s -= l + r;
+            (Side::Right, &right)
+        } else {
+            (Side::Left, &left)
+        };
+        moves.extend(
+            compartment
+                .iter()
+                .enumerate()
+                .filter(|&(_, &c)| c == item)
+                .map(|(position, _)| ItemMove {
+                    item,
+                    from,
+                    position,
+                }),
+        );
+    }
+    moves.sort_by_key(|m| (m.from, m.position));
+
+    Ok(RepackPlan { moves, cost })
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepackSummary {
+    pub moves: usize,
+    /// Total priority of every item moved.
+    pub cost: u32,
+    /// Rucksacks that cannot be repacked or hold unknown items, by position
+    /// in the input.
+    pub impossible: Vec<usize>,
+}
+
+/// Plan every rucksack and add up the moves that are possible.
+pub fn repack_all(sacks: &[Rucksack]) -> RepackSummary {
+    repack_all_with(sacks, &LetterScheme)
+}
+
+pub fn repack_all_with(sacks: &[Rucksack], scheme: &dyn PriorityScheme) -> RepackSummary {
+    let mut summary = RepackSummary::default();
+    for (idx, sack) in sacks.iter().enumerate() {
+        match plan_repack_with(sack, scheme) {
+            Ok(plan) => {
+                summary.moves += plan.len();
+                summary.cost += plan.cost();
+            }
+            Err(_) => summary.impossible.push(idx),
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_misplaced_item() {
+        let sack = Rucksack::parse("vJrwpWtwJgWrhcsFMMfFFhFp");
+        let plan = plan_repack(&sack).unwrap();
+        let packed = plan.apply(&sack);
+        assert!(packed.shared_items().is_empty());
+        assert_eq!(packed.left_sack().len(), packed.right_sack().len());
+    }
+
+    #[test]
+    fn test_already_packed() {
+        let plan = plan_repack(&Rucksack::parse("abcdEFGH")).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(plan.cost(), 0);
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        assert_eq!(
+            plan_repack(&Rucksack::parse("aaab")),
+            Err(RepackError::Unbalanced { size: 2 })
+        );
+    }
+
+    #[test]
+    fn test_prefers_cheaper_items() {
+        // Moving 'a' and 'b' or 'a' and 'Z' both take two moves
+        let plan = plan_repack(&Rucksack::parse("abZa")).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan.cost(), 3);
+    }
+}
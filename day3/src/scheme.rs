@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+pub trait PriorityScheme {
+    /// The priority of `item`, or `None` if the scheme doesn't know it.
+    fn priority(&self, item: char) -> Option<u32>;
+
+    fn contains(&self, item: char) -> bool {
+        self.priority(item).is_some()
+    }
+
+    /// Whether every item is in `[a-zA-Z]`, so sets of them fit an `ItemSet`.
+    fn letters_only(&self) -> bool {
+        false
+    }
+}
+
+/// The puzzle's scheme: `a` to `z` are 1 to 26, `A` to `Z` are 27 to 52.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LetterScheme;
+
+impl PriorityScheme for LetterScheme {
+    fn priority(&self, item: char) -> Option<u32> {
+        match item {
+            'a'..='z' => Some(u32::from(item) - u32::from('a') + 1),
+            'A'..='Z' => Some(u32::from(item) - u32::from('A') + 27),
+            _ => None,
+        }
+    }
+
+    fn letters_only(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SchemeError {
+    pub line: usize,
+    msg: String,
+}
+
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.msg)
+        } else {
+            write!(f, "line {}: {}", self.line, self.msg)
+        }
+    }
+}
+
+impl Error for SchemeError {}
+
+impl SchemeError {
+    pub fn new(line: usize, msg: &str) -> Self {
+        SchemeError {
+            line,
+            msg: String::from(msg),
+        }
+    }
+}
+
+/// Priorities listed item by item.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableScheme {
+    priorities: HashMap<char, u32>,
+}
+
+impl TableScheme {
+    pub fn new(entries: &[(char, u32)]) -> Self {
+        TableScheme {
+            priorities: entries.iter().copied().collect(),
+        }
+    }
+
+    pub fn from_path(path: &str) -> Result<TableScheme, SchemeError> {
+        let table = fs::read_to_string(path)
+            .map_err(|e| SchemeError::new(0, &format!("could not read {}: {}", path, e)))?;
+        table.parse()
+    }
+
+    pub fn len(&self) -> usize {
+        self.priorities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.priorities.is_empty()
+    }
+}
+
+impl PriorityScheme for TableScheme {
+    fn priority(&self, item: char) -> Option<u32> {
+        self.priorities.get(&item).copied()
+    }
+
+    fn letters_only(&self) -> bool {
+        self.priorities.keys().all(|c| c.is_ascii_alphabetic())
+    }
+}
+
+impl FromStr for TableScheme {
+    type Err = SchemeError;
+
+    /// Parse a table with one `<item> <priority>` pair per non-empty line.
+    /// Items are single characters and may appear only once.
+    fn from_str(table: &str) -> Result<Self, Self::Err> {
+        let mut priorities = HashMap::new();
+
+        for (idx, line) in table.lines().enumerate() {
+            let line_num = idx + 1;
+            let err = |msg: &str| SchemeError::new(line_num, msg);
+
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let (item, priority) = match tokens.as_slice() {
+                [] => continue,
+                [item, priority] => (*item, *priority),
+                _ => return Err(err("expected an item and a priority")),
+            };
+
+            let mut chars = item.chars();
+            let item = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(err(&format!("item {:?} is not a single character", item))),
+            };
+            let priority: u32 = priority
+                .parse()
+                .map_err(|_| err(&format!("invalid priority {:?}", priority)))?;
+
+            if priorities.insert(item, priority).is_some() {
+                return Err(err(&format!("duplicate item {:?}", item)));
+            }
+        }
+
+        Ok(TableScheme { priorities })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_scheme() {
+        assert_eq!(LetterScheme.priority('a'), Some(1));
+        assert_eq!(LetterScheme.priority('Z'), Some(52));
+        assert_eq!(LetterScheme.priority('é'), None);
+        assert!(!LetterScheme.contains('1'));
+    }
+
+    #[test]
+    fn test_table_scheme() {
+        let scheme: TableScheme = "a 1\né 30\n\n🍎 100\n".parse().unwrap();
+        assert_eq!(scheme.len(), 3);
+        assert_eq!(scheme.priority('é'), Some(30));
+        assert_eq!(scheme.priority('🍎'), Some(100));
+        assert_eq!(scheme.priority('b'), None);
+    }
+
+    #[test]
+    fn test_table_scheme_errors() {
+        let err = |table: &str| table.parse::<TableScheme>().unwrap_err().line;
+        assert_eq!(err("a 1\nab 2"), 2);
+        assert_eq!(err("a 1\nb"), 2);
+        assert_eq!(err("a x"), 1);
+        assert_eq!(err("a 1\n\na 2"), 3);
+    }
+}
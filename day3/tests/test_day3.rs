@@ -1,6 +1,11 @@
 #[cfg(test)]
 mod tests_day3 {
 
+    use day3::error::ParseError;
+    use day3::inventory::Inventory;
+    use day3::partition::partition_rucksacks;
+    use day3::repack::{plan_repack, repack_all};
+    use day3::scheme::{LetterScheme, TableScheme};
     use day3::*;
 
     #[test]
@@ -13,33 +18,163 @@ mod tests_day3 {
     #[test]
     fn test_rucksack_findshared() {
         let sack = Rucksack::parse("abXcdEFGHX");
-        assert_eq!(sack.find_shared_item(), 'X');
+        assert_eq!(sack.find_shared_item(), Some('X'));
+        assert_eq!(Rucksack::parse("abcd").find_shared_item(), None);
+        assert_eq!(Rucksack::parse("abab").find_shared_item(), None);
     }
 
     #[test]
     fn test_pt1() {
         let sacks = parse_rucksacks("input_test.txt");
-        assert_eq!(overlap_priority(&sacks), 157);
+        assert_eq!(overlap_priority(&sacks), Some(157));
     }
 
     #[test]
     fn test_team_parse() {
-        let teams = parse_teams("input_test.txt");
+        let teams = parse_teams("input_test.txt").unwrap();
         assert_eq!(teams.len(), 2);
 
         assert_eq!(
-            teams[0].group_a,
+            teams[0].members[0],
             "vJrwpWtwJgWrhcsFMMfFFhFp".chars().collect::<Vec<char>>()
         );
         assert_eq!(
-            teams[1].group_c,
+            teams[1].members[2],
             "CrZsJsPPZsGzwwsLwLmpwMDw".chars().collect::<Vec<char>>()
         );
     }
 
     #[test]
     fn test_pt2() {
-        let teams = parse_teams("input_test.txt");
-        assert_eq!(badge_priorities(&teams), 70);
+        let teams = parse_teams("input_test.txt").unwrap();
+        assert_eq!(badge_priorities(&teams), Some(70));
+    }
+
+    #[test]
+    fn test_team_sizes() {
+        let pairs = parse_teams_with("input_test.txt", 2).unwrap();
+        assert_eq!(pairs.len(), 3);
+        assert!(pairs.iter().all(|t| t.len() == 2));
+
+        let everyone = parse_teams_with("input_test.txt", 6).unwrap();
+        assert_eq!(everyone.len(), 1);
+        assert!(everyone[0].shared_items().is_empty());
+        assert_eq!(everyone[0].badge(), None);
+        assert_eq!(badge_priorities(&everyone), None);
+    }
+
+    #[test]
+    fn test_team_errors() {
+        match parse_teams_with("input_test.txt", 4) {
+            Err(ParseError::IncompleteTeam {
+                line,
+                expected,
+                found,
+            }) => assert_eq!((line, expected, found), (5, 4, 2)),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(matches!(
+            parse_teams_with("input_test.txt", 0),
+            Err(ParseError::InvalidGroupSize)
+        ));
+    }
+
+    #[test]
+    fn test_blank_separated_teams() {
+        let input = "\nvJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\n\n\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\nttgJtRGJQctTZtZT\nCrZsJsPPZsGzwwsLwLmpwMDw\n";
+        let teams = read_teams(input.as_bytes(), TeamLayout::BlankSeparated).unwrap();
+        assert_eq!(teams.len(), 2);
+        assert_eq!(teams[0].len(), 2);
+        assert_eq!(teams[1].len(), 3);
+        assert!(teams[0].shared_items().contains('r'));
+        assert_eq!(teams[1].badge(), Some('Z'));
+    }
+
+    #[test]
+    fn test_partition_shuffled() {
+        let mut sacks = parse_rucksacks("input_test.txt");
+        sacks.swap(1, 4);
+        sacks.swap(0, 5);
+
+        let partition = partition_rucksacks(&sacks);
+        assert!(partition.is_complete());
+        assert_eq!(partition.teams.len(), 2);
+        assert_eq!(partition.badge_priorities(), Some(70));
+        assert!(!partition.unique);
+    }
+
+    #[test]
+    fn test_repack_batch() {
+        let sacks = parse_rucksacks("input_test.txt");
+        for sack in sacks.iter() {
+            let packed = plan_repack(sack).unwrap().apply(sack);
+            assert!(packed.shared_items().is_empty());
+        }
+
+        let summary = repack_all(&sacks);
+        assert_eq!((summary.moves, summary.cost), (18, 345));
+        assert!(summary.impossible.is_empty());
+    }
+
+    #[test]
+    fn test_rucksack_unicode() {
+        let sack = Rucksack::parse("aé🍎bü🍎");
+        assert_eq!(sack.left_sack(), "aé🍎");
+        assert_eq!(sack.right_sack(), "bü🍎");
+        assert_eq!(sack.find_shared_item(), Some('🍎'));
+    }
+
+    #[test]
+    fn test_table_scheme() {
+        let scheme = TableScheme::from_path("scheme_test.txt").unwrap();
+        let input = "aé🍎bü🍎\n\néab🍐üé\n";
+        let sacks = read_rucksacks(input.as_bytes(), &scheme).unwrap();
+        assert_eq!(sacks.len(), 2);
+        assert_eq!(overlap_priority_with(&sacks, &scheme), Some(110));
+        assert_eq!(overlap_priority_with(&sacks, &LetterScheme), None);
+    }
+
+    #[test]
+    fn test_unknown_items() {
+        let scheme = TableScheme::from_path("scheme_test.txt").unwrap();
+        match read_rucksacks("abab\nab🍌b\n".as_bytes(), &scheme) {
+            Err(ParseError::UnknownItem { line, column, item }) => {
+                assert_eq!((line, column, item), (2, 3, '🍌'))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(matches!(
+            read_rucksacks("aé🍎".as_bytes(), &scheme),
+            Err(ParseError::OddLength { line: 1, len: 3 })
+        ));
+        assert!(matches!(
+            read_teams("abcd\nab1d\nabcd\n".as_bytes(), TeamLayout::Fixed(3)),
+            Err(ParseError::UnknownItem { line: 2, .. })
+        ));
+        assert_eq!(
+            parse_rucksacks_with("input_test.txt", &LetterScheme)
+                .unwrap()
+                .len(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_inventory_from_teams() {
+        let teams = parse_teams("input_test.txt").unwrap();
+        let inventory = Inventory::from_teams(&teams);
+        assert_eq!(inventory.num_sacks(), 6);
+        assert!(inventory.in_every_sack().is_empty());
+
+        let carriers: Vec<(usize, Option<usize>)> = inventory
+            .carriers('Z')
+            .iter()
+            .map(|h| (h.sack, inventory.team_of(h.sack)))
+            .collect();
+        assert_eq!(
+            carriers,
+            vec![(1, Some(0)), (3, Some(1)), (4, Some(1)), (5, Some(1))]
+        );
+        assert_eq!(inventory.most_common(1), vec![('w', 4)]);
     }
 }
@@ -0,0 +1,246 @@
+use std::fmt::Write;
+
+use crate::range::Discrete;
+use crate::Range;
+
+/// One elf's assignment: elf `elf` (0 or 1) of line `pair`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Assignment {
+    pub pair: usize,
+    pub elf: usize,
+    pub range: Range,
+}
+
+/// Flatten pairs into one assignment per elf, in input order.
+pub fn assignments(pairs: &[(Range, Range)]) -> Vec<Assignment> {
+    pairs
+        .iter()
+        .enumerate()
+        .flat_map(|(pair, &(a, b))| {
+            [
+                Assignment {
+                    pair,
+                    elf: 0,
+                    range: a,
+                },
+                Assignment {
+                    pair,
+                    elf: 1,
+                    range: b,
+                },
+            ]
+        })
+        .collect()
+}
+
+/// A run of sections covered by the same number of elves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub range: Range,
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageReport {
+    /// Every section from the lowest assigned to the highest, split where
+    /// the coverage changes.
+    pub segments: Vec<Segment>,
+    /// Sections in that span nobody covers.
+    pub uncovered: Vec<Range>,
+    pub max_depth: usize,
+    /// Where coverage reaches `max_depth`.
+    pub max_depth_at: Vec<Range>,
+    /// `histogram[d]` is the number of sections covered exactly `d` times.
+    pub histogram: Vec<u64>,
+    /// Assignments whose every section someone else also covers.
+    pub redundant: Vec<Assignment>,
+}
+
+/// Range minimum queries in O(1) after O(n log n) setup.
+struct SparseMin {
+    levels: Vec<Vec<usize>>,
+}
+
+impl SparseMin {
+    fn new(values: Vec<usize>) -> Self {
+        let mut levels = vec![values];
+        let mut width = 1;
+        while 2 * width <= levels[0].len() {
+            let prev = levels.last().unwrap();
+            let next = (0..prev.len() - width)
+                .map(|i| prev[i].min(prev[i + width]))
+                .collect();
+            levels.push(next);
+            width *= 2;
+        }
+        SparseMin { levels }
+    }
+
+    /// Minimum of `values[lo..=hi]`.
+    fn min(&self, lo: usize, hi: usize) -> usize {
+        let level = (hi - lo + 1).ilog2() as usize;
+        let width = 1 << level;
+        self.levels[level][lo].min(self.levels[level][hi + 1 - width])
+    }
+}
+
+impl CoverageReport {
+    /// Sweep over every assignment's endpoints in O(n log n).
+    pub fn new(pairs: &[(Range, Range)]) -> Self {
+        let elves = assignments(pairs);
+
+        // Coverage changes by +1 at each start and -1 just past each end
+        let mut events: Vec<(i32, isize)> = Vec::with_capacity(2 * elves.len());
+        for a in elves.iter() {
+            events.push((a.range.lo, 1));
+            if let Some(past_end) = a.range.hi.succ() {
+                events.push((past_end, -1));
+            }
+        }
+        events.sort_unstable();
+
+        let last = elves.iter().map(|a| a.range.hi).max();
+        let mut segments: Vec<Segment> = vec![];
+        let mut depth: isize = 0;
+        let mut idx = 0;
+        while idx < events.len() {
+            let pos = events[idx].0;
+            while idx < events.len() && events[idx].0 == pos {
+                depth += events[idx].1;
+                idx += 1;
+            }
+            let end = match events.get(idx) {
+                Some(&(next, _)) => next - 1,
+                None => break,
+            };
+            segments.push(Segment {
+                range: Range::new(pos, end),
+                depth: depth as usize,
+            });
+        }
+        // Sections up to the maximum value never see an end event
+        if let (Some(last), Some(&(pos, _))) = (last, events.last()) {
+            if depth > 0 && pos <= last {
+                segments.push(Segment {
+                    range: Range::new(pos, last),
+                    depth: depth as usize,
+                });
+            }
+        }
+
+        let max_depth = segments.iter().map(|s| s.depth).max().unwrap_or(0);
+        let mut histogram = vec![0; max_depth + 1];
+        for s in segments.iter() {
+            histogram[s.depth] += s.range.len();
+        }
+        if elves.is_empty() {
+            histogram.clear();
+        }
+
+        let ranges_at = |depth: usize| -> Vec<Range> {
+            segments
+                .iter()
+                .filter(|s| s.depth == depth)
+                .map(|s| s.range)
+                .collect()
+        };
+        let uncovered = ranges_at(0);
+        let max_depth_at = if elves.is_empty() {
+            vec![]
+        } else {
+            ranges_at(max_depth)
+        };
+
+        let redundant = if segments.is_empty() {
+            vec![]
+        } else {
+            let mins = SparseMin::new(segments.iter().map(|s| s.depth).collect());
+            let segment_of = |x: i32| segments.partition_point(|s| s.range.hi < x);
+            elves
+                .iter()
+                .filter(|a| mins.min(segment_of(a.range.lo), segment_of(a.range.hi)) >= 2)
+                .copied()
+                .collect()
+        };
+
+        CoverageReport {
+            segments,
+            uncovered,
+            max_depth,
+            max_depth_at,
+            histogram,
+            redundant,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let join = |ranges: &[Range]| {
+            let parts: Vec<String> = ranges.iter().map(|r| r.to_string()).collect();
+            if parts.is_empty() {
+                String::from("none")
+            } else {
+                parts.join(", ")
+            }
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(out, "Uncovered sections: {}", join(&self.uncovered));
+        let _ = writeln!(
+            out,
+            "Maximum overlap: {} at {}",
+            self.max_depth,
+            join(&self.max_depth_at)
+        );
+        let _ = writeln!(out, "Coverage histogram:");
+        for (depth, &sections) in self.histogram.iter().enumerate() {
+            if sections == 0 {
+                continue;
+            }
+            let _ = writeln!(out, "{:>6} elves: {:>6} sections", depth, sections);
+        }
+        let _ = writeln!(out, "Redundant assignments: {}", self.redundant.len());
+        for a in self.redundant.iter() {
+            let _ = writeln!(out, "  line {} elf {}: {}", a.pair + 1, a.elf + 1, a.range);
+        }
+        out
+    }
+
+    /// One row per segment of constant coverage.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("lo,hi,sections,depth\n");
+        for s in self.segments.iter() {
+            let _ = writeln!(
+                out,
+                "{},{},{},{}",
+                s.range.lo,
+                s.range.hi,
+                s.range.len(),
+                s.depth
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_min() {
+        let values = vec![5, 3, 8, 1, 9, 2, 7];
+        let mins = SparseMin::new(values.clone());
+        for lo in 0..values.len() {
+            for hi in lo..values.len() {
+                assert_eq!(mins.min(lo, hi), *values[lo..=hi].iter().min().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_bounds() {
+        let report = CoverageReport::new(&[(Range::new(0, i32::MAX), Range::new(5, i32::MAX))]);
+        assert_eq!(report.max_depth_at, vec![Range::new(5, i32::MAX)]);
+        assert_eq!(report.redundant.len(), 1);
+    }
+}
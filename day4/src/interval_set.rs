@@ -0,0 +1,146 @@
+use std::iter::FromIterator;
+
+use crate::range::{Discrete, Range};
+
+/// A set of values stored as sorted, disjoint ranges. Ranges that overlap or
+/// touch are merged on insert, so each stored range is as long as possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T = i32> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: vec![] }
+    }
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    /// Number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values covered.
+    pub fn size(&self) -> u64 {
+        self.ranges
+            .iter()
+            .fold(0u64, |sum, r| sum.saturating_add(r.len()))
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        let start = self
+            .ranges
+            .partition_point(|r| r.hi < range.lo && !r.is_adjacent(&range));
+        let end = self
+            .ranges
+            .partition_point(|r| r.lo <= range.hi || r.is_adjacent(&range));
+
+        let mut merged = range;
+        if start < end {
+            merged.lo = merged.lo.min(self.ranges[start].lo);
+            merged.hi = merged.hi.max(self.ranges[end - 1].hi);
+        }
+        self.ranges.splice(start..end, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        let start = self.ranges.partition_point(|r| r.hi < range.lo);
+        let end = self.ranges.partition_point(|r| r.lo <= range.hi);
+
+        let kept: Vec<Range<T>> = self.ranges[start..end]
+            .iter()
+            .flat_map(|r| r.difference(&range))
+            .collect();
+        self.ranges.splice(start..end, kept);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.ranges.partition_point(|r| r.hi < value);
+        self.ranges.get(idx).is_some_and(|r| r.contains(value))
+    }
+
+    /// Whether every value of `range` is in the set.
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        let idx = self.ranges.partition_point(|r| r.hi < range.lo);
+        self.ranges
+            .get(idx)
+            .is_some_and(|r| range.is_contained_in(r))
+    }
+
+    /// The stored ranges, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter()
+    }
+}
+
+impl<T: Discrete> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl<'a, T: Discrete> IntoIterator for &'a IntervalSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(set: &IntervalSet) -> Vec<(i32, i32)> {
+        set.iter().map(|r| (r.lo, r.hi)).collect()
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let mut set: IntervalSet = [Range::new(1, 3), Range::new(8, 9), Range::new(12, 15)]
+            .into_iter()
+            .collect();
+        assert_eq!(ranges(&set), vec![(1, 3), (8, 9), (12, 15)]);
+
+        // Touches the first range and overlaps the second
+        set.insert(Range::new(4, 8));
+        assert_eq!(ranges(&set), vec![(1, 9), (12, 15)]);
+
+        set.insert(Range::new(10, 11));
+        assert_eq!(ranges(&set), vec![(1, 15)]);
+        assert_eq!(set.size(), 15);
+    }
+
+    #[test]
+    fn test_remove_splits() {
+        let mut set: IntervalSet = [Range::new(1, 10), Range::new(20, 30)]
+            .into_iter()
+            .collect();
+        set.remove(Range::new(5, 22));
+        assert_eq!(ranges(&set), vec![(1, 4), (23, 30)]);
+
+        set.remove(Range::new(2, 3));
+        assert_eq!(ranges(&set), vec![(1, 1), (4, 4), (23, 30)]);
+        assert!(set.contains(4));
+        assert!(!set.contains(3));
+        assert!(set.contains_range(&Range::new(24, 30)));
+        assert!(!set.contains_range(&Range::new(4, 23)));
+
+        set.remove(Range::new(-100, 100));
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::BTreeSet;
+
+use crate::coverage::{assignments, Assignment};
+use crate::Range;
+
+/// Orders assignments by where they start.
+type Key = (i32, i32, usize, usize);
+
+fn key(a: &Assignment) -> Key {
+    (a.range.lo, a.range.hi, a.pair, a.elf)
+}
+
+fn assignment((lo, hi, pair, elf): Key) -> Assignment {
+    Assignment {
+        pair,
+        elf,
+        range: Range { lo, hi },
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: [Option<usize>; 2],
+    /// Assignments covering this node's whole span but not its parent's.
+    here: BTreeSet<Key>,
+}
+
+/// Index over assignments for "who covers section s" and "who overlaps
+/// [a, b]" queries.
+///
+/// Each assignment is stored in a segment tree over every possible `i32`
+/// section, split across the nodes whose spans it covers, and in a set
+/// ordered by start. The segment tree is at most 32 levels deep and an
+/// assignment lands on at most two nodes per level, so inserts and deletes
+/// touch up to about 64 nodes with a set operation at each: O(32 log n).
+/// A query reporting k assignments costs O(32 + log n + k).
+///
+/// Only nodes in use are kept; a delete frees any node left with nothing
+/// stored at or below it, for reuse by later inserts.
+#[derive(Debug, Clone)]
+pub struct IntervalTree {
+    nodes: Vec<Node>,
+    /// Slots in `nodes` that were freed and can be reused.
+    free: Vec<usize>,
+    by_start: BTreeSet<Key>,
+}
+
+impl Default for IntervalTree {
+    fn default() -> Self {
+        IntervalTree {
+            nodes: vec![Node::default()],
+            free: vec![],
+            by_start: BTreeSet::new(),
+        }
+    }
+}
+
+const ROOT_SPAN: (i64, i64) = (i32::MIN as i64, i32::MAX as i64);
+
+impl IntervalTree {
+    pub fn new() -> Self {
+        IntervalTree::default()
+    }
+
+    /// Index every elf of every pair.
+    pub fn from_pairs(pairs: &[(Range, Range)]) -> Self {
+        let mut tree = IntervalTree::new();
+        for a in assignments(pairs) {
+            tree.insert(a);
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_start.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_start.is_empty()
+    }
+
+    /// Add `a`, returning false if it was already there.
+    pub fn insert(&mut self, a: Assignment) -> bool {
+        let key = key(&a);
+        if !self.by_start.insert(key) {
+            return false;
+        }
+        self.place(0, ROOT_SPAN, key, true);
+        true
+    }
+
+    /// Drop `a`, returning false if it wasn't there.
+    pub fn remove(&mut self, a: &Assignment) -> bool {
+        let key = key(a);
+        if !self.by_start.remove(&key) {
+            return false;
+        }
+        self.place(0, ROOT_SPAN, key, false);
+        true
+    }
+
+    /// Number of nodes in use, including the root.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    fn alloc(&mut self) -> usize {
+        match self.free.pop() {
+            Some(idx) => idx,
+            None => {
+                self.nodes.push(Node::default());
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Add or remove `key` at every node whose span it covers exactly,
+    /// freeing children left empty. Returns whether `node` is now empty.
+    fn place(&mut self, node: usize, (lo, hi): (i64, i64), key: Key, add: bool) -> bool {
+        let (key_lo, key_hi) = (key.0 as i64, key.1 as i64);
+        if key_lo <= lo && hi <= key_hi {
+            if add {
+                self.nodes[node].here.insert(key);
+            } else {
+                self.nodes[node].here.remove(&key);
+            }
+            return self.is_empty_node(node);
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        for (side, span) in [(0, (lo, mid)), (1, (mid + 1, hi))] {
+            if key_hi < span.0 || span.1 < key_lo {
+                continue;
+            }
+            let child = match self.nodes[node].children[side] {
+                Some(child) => child,
+                None => {
+                    let child = self.alloc();
+                    self.nodes[node].children[side] = Some(child);
+                    child
+                }
+            };
+            if self.place(child, span, key, add) {
+                self.nodes[node].children[side] = None;
+                self.free.push(child);
+            }
+        }
+        self.is_empty_node(node)
+    }
+
+    fn is_empty_node(&self, node: usize) -> bool {
+        let node = &self.nodes[node];
+        node.here.is_empty() && node.children.iter().all(|c| c.is_none())
+    }
+
+    /// Every assignment covering `section`, in no particular order.
+    pub fn covering(&self, section: i32) -> Vec<Assignment> {
+        let mut found: Vec<Key> = vec![];
+        let (mut lo, mut hi) = ROOT_SPAN;
+        let mut node = Some(0);
+        while let Some(idx) = node {
+            found.extend(self.nodes[idx].here.iter().copied());
+
+            let mid = lo + (hi - lo) / 2;
+            let side = if (section as i64) <= mid {
+                hi = mid;
+                0
+            } else {
+                lo = mid + 1;
+                1
+            };
+            node = self.nodes[idx].children[side];
+        }
+        found.into_iter().map(assignment).collect()
+    }
+
+    /// Every assignment sharing a section with `range`: those covering its
+    /// first section, then those starting inside it.
+    pub fn overlapping(&self, range: &Range) -> Vec<Assignment> {
+        let mut found = self.covering(range.lo);
+        if let Some(next) = range.lo.checked_add(1).filter(|&next| next <= range.hi) {
+            let starts_inside = self
+                .by_start
+                .range((next, i32::MIN, 0, 0)..=(range.hi, i32::MAX, usize::MAX, usize::MAX));
+            found.extend(starts_inside.copied().map(assignment));
+        }
+        found
+    }
+
+    /// Every assignment, ordered by start.
+    pub fn iter(&self) -> impl Iterator<Item = Assignment> + '_ {
+        self.by_start.iter().copied().map(assignment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tiny LCG so the property tests are reproducible without a dependency.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) % n
+        }
+
+        fn range(&mut self) -> Range {
+            let lo = self.below(60) as i32 - 10;
+            Range::new(lo, lo + self.below(15) as i32)
+        }
+    }
+
+    fn scan(live: &[Assignment], query: &Range) -> Vec<Assignment> {
+        let mut found: Vec<Assignment> = live
+            .iter()
+            .filter(|a| a.range.is_overlapping(query))
+            .copied()
+            .collect();
+        found.sort_by_key(key);
+        found
+    }
+
+    fn sorted(mut found: Vec<Assignment>) -> Vec<Assignment> {
+        found.sort_by_key(key);
+        found
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        for seed in 0..20 {
+            let mut rng = Lcg(seed);
+            let mut tree = IntervalTree::new();
+            let mut live: Vec<Assignment> = vec![];
+
+            for step in 0..300 {
+                if !live.is_empty() && rng.below(3) == 0 {
+                    let a = live.swap_remove(rng.below(live.len() as u64) as usize);
+                    assert!(tree.remove(&a));
+                    assert!(!tree.remove(&a));
+                } else {
+                    let a = Assignment {
+                        pair: step,
+                        elf: rng.below(2) as usize,
+                        range: rng.range(),
+                    };
+                    assert!(tree.insert(a));
+                    live.push(a);
+                }
+                assert_eq!(tree.len(), live.len());
+
+                let query = rng.range();
+                assert_eq!(sorted(tree.overlapping(&query)), scan(&live, &query));
+                let section = rng.below(70) as i32 - 15;
+                assert_eq!(
+                    sorted(tree.covering(section)),
+                    scan(&live, &Range::new(section, section))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_sections() {
+        let mut tree = IntervalTree::new();
+        let wide = Assignment {
+            pair: 0,
+            elf: 0,
+            range: Range::new(i32::MIN, i32::MAX),
+        };
+        let top = Assignment {
+            pair: 0,
+            elf: 1,
+            range: Range::new(i32::MAX, i32::MAX),
+        };
+        tree.insert(wide);
+        tree.insert(top);
+
+        assert_eq!(tree.covering(i32::MIN), vec![wide]);
+        assert_eq!(tree.covering(i32::MAX), vec![wide, top]);
+        assert_eq!(tree.overlapping(&Range::new(i32::MAX, i32::MAX)).len(), 2);
+    }
+
+    #[test]
+    fn test_remove_frees_nodes() {
+        let mut rng = Lcg(3);
+        let mut tree = IntervalTree::new();
+        for round in 0..50 {
+            let batch: Vec<Assignment> = (0..20)
+                .map(|pair| Assignment {
+                    pair,
+                    elf: 0,
+                    range: Range::new(round * 1000, round * 1000 + rng.below(500) as i32),
+                })
+                .collect();
+            for a in batch.iter() {
+                tree.insert(*a);
+            }
+            for a in batch.iter() {
+                tree.remove(a);
+            }
+            assert_eq!(tree.num_nodes(), 1);
+        }
+        assert!(tree.nodes.len() < 20 * 64);
+    }
+}
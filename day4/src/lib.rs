@@ -1,36 +1,15 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-#[derive(Debug, Clone, Copy)]
-pub struct Range {
-    pub lo: i32,
-    pub hi: i32,
-}
-
-impl Range {
-    pub fn parse(lo_hi: &str) -> Range {
-        let parsed: Vec<i32> = lo_hi
-            .split('-')
-            .map(|s| s.parse::<i32>().unwrap())
-            .collect();
-        if parsed.len() != 2 {
-            panic!("Expected a string of format <int>-<int>");
-        }
-        Range {
-            lo: parsed[0],
-            hi: parsed[1],
-        }
-    }
+pub mod coverage;
+pub mod interval_set;
+pub mod interval_tree;
+pub mod range;
+pub mod schedule;
 
-    pub fn is_contained_in(&self, other: &Range) -> bool {
-        (self.lo >= other.lo) && (self.hi <= other.hi)
-    }
-
-    pub fn is_overlapping(&self, other: &Range) -> bool {
-        ((self.lo >= other.lo) && (self.lo <= other.hi))
-            || ((self.hi >= other.lo) && (self.hi <= other.hi))
-    }
-}
+pub use interval_set::IntervalSet;
+pub use range::Range;
+use range::{RangeError, RangeErrorKind};
 
 pub fn count_contained_ranges(ranges: &[(Range, Range)]) -> usize {
     ranges
@@ -40,20 +19,35 @@ pub fn count_contained_ranges(ranges: &[(Range, Range)]) -> usize {
 }
 
 pub fn count_overlapping_ranges(ranges: &[(Range, Range)]) -> usize {
-    ranges
-        .iter()
-        .filter(|p| p.0.is_overlapping(&p.1) || p.1.is_overlapping(&p.0))
-        .count()
+    ranges.iter().filter(|p| p.0.is_overlapping(&p.1)).count()
+}
+
+/// Parse one `<range>,<range>` line.
+pub fn parse_range_pair(line: &str) -> Result<(Range, Range), RangeError> {
+    let (a, b) = line
+        .split_once(',')
+        .filter(|(_, b)| !b.contains(','))
+        .ok_or_else(|| RangeError::new(0, line, RangeErrorKind::WrongCount))?;
+    Ok((Range::parse(a.trim())?, Range::parse(b.trim())?))
 }
 
-pub fn parse_range_file(path: &str) -> Vec<(Range, Range)> {
+/// Read one pair of ranges per line, skipping blank lines.
+pub fn read_ranges<R: BufRead>(reader: R) -> Result<Vec<(Range, Range)>, RangeError> {
     let mut ranges: Vec<(Range, Range)> = vec![];
 
-    let reader = BufReader::new(File::open(path).expect("File not found"));
-    for line in reader.lines() {
-        let parsed_ranges: Vec<Range> = line.unwrap().split(',').map(Range::parse).collect();
-        ranges.push((parsed_ranges[0], parsed_ranges[1]));
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| RangeError::new(idx + 1, "", RangeErrorKind::Io(e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let pair = parse_range_pair(&line).map_err(|e| RangeError { line: idx + 1, ..e })?;
+        ranges.push(pair);
     }
 
-    ranges
+    Ok(ranges)
+}
+
+pub fn parse_range_file(path: &str) -> Result<Vec<(Range, Range)>, RangeError> {
+    let file = File::open(path).map_err(|e| RangeError::new(0, path, RangeErrorKind::Io(e)))?;
+    read_ranges(BufReader::new(file))
 }
@@ -1,7 +1,80 @@
+use day4::coverage::CoverageReport;
+use day4::schedule::{max_disjoint, schedule_shifts};
 use day4::*;
 
+use std::env;
+use std::process;
+
+fn usage() -> ! {
+    eprintln!("Usage: day4 [PATH]");
+    eprintln!("       day4 coverage [--csv] [PATH]");
+    eprintln!("       day4 schedule [--disjoint] [PATH]");
+    process::exit(2);
+}
+
+fn parse_ranges(path: &str) -> Vec<(Range, Range)> {
+    parse_range_file(path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    })
+}
+
+fn coverage(args: impl Iterator<Item = String>) {
+    let mut csv = false;
+    let mut path = String::from("day4/input.txt");
+    for arg in args {
+        match arg.as_str() {
+            "--csv" => csv = true,
+            _ if arg.starts_with('-') => usage(),
+            _ => path = arg,
+        }
+    }
+
+    let report = CoverageReport::new(&parse_ranges(&path));
+    if csv {
+        print!("{}", report.to_csv());
+    } else {
+        print!("{}", report.to_text());
+    }
+}
+
+fn schedule(args: impl Iterator<Item = String>) {
+    let mut disjoint = false;
+    let mut path = String::from("day4/input.txt");
+    for arg in args {
+        match arg.as_str() {
+            "--disjoint" => disjoint = true,
+            _ if arg.starts_with('-') => usage(),
+            _ => path = arg,
+        }
+    }
+
+    let ranges = parse_ranges(&path);
+    if disjoint {
+        let chosen = max_disjoint(&ranges);
+        println!("{} non-overlapping assignments", chosen.len());
+        for a in chosen {
+            println!("line {} elf {}: {}", a.pair + 1, a.elf + 1, a.range);
+        }
+    } else {
+        let schedule = schedule_shifts(&ranges);
+        println!("{} shifts", schedule.num_shifts);
+        for (idx, [a, b]) in schedule.shifts.iter().enumerate() {
+            println!("line {}: shifts {}, {}", idx + 1, a + 1, b + 1);
+        }
+    }
+}
+
 fn main() {
-    let ranges = parse_range_file("day4/input.txt");
-    println!("Part 1: {}", count_contained_ranges(&ranges));
-    println!("Part 2: {}", count_overlapping_ranges(&ranges));
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("coverage") => coverage(args),
+        Some("schedule") => schedule(args),
+        Some(arg) if arg.starts_with('-') => usage(),
+        path => {
+            let ranges = parse_ranges(path.unwrap_or("day4/input.txt"));
+            println!("Part 1: {}", count_contained_ranges(&ranges));
+            println!("Part 2: {}", count_overlapping_ranges(&ranges));
+        }
+    }
 }
@@ -0,0 +1,253 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Bounds with a well-defined next and previous value, so inclusive ranges
+/// can be measured, split and joined end to end.
+pub trait Discrete: Ord + Copy {
+    fn succ(self) -> Option<Self>;
+    fn pred(self) -> Option<Self>;
+    /// Number of values in `lo..=hi`, saturating at `u64::MAX`.
+    fn count(lo: Self, hi: Self) -> u64;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {
+        $(
+            impl Discrete for $t {
+                fn succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn count(lo: Self, hi: Self) -> u64 {
+                    if hi < lo {
+                        return 0;
+                    }
+                    (hi as i128 - lo as i128 + 1).min(u64::MAX as i128) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_discrete!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+#[derive(Debug)]
+pub enum RangeErrorKind {
+    Io(io::Error),
+    MissingSeparator,
+    InvalidBound,
+    /// The low bound is above the high bound.
+    Reversed,
+    /// A line doesn't hold exactly two ranges.
+    WrongCount,
+}
+
+#[derive(Debug)]
+pub struct RangeError {
+    /// 1-based line of the input, or 0 when parsing a lone range.
+    pub line: usize,
+    pub text: String,
+    pub kind: RangeErrorKind,
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line != 0 {
+            write!(f, "line {}: ", self.line)?;
+        }
+        match &self.kind {
+            RangeErrorKind::Io(e) => write!(f, "could not read line: {}", e),
+            RangeErrorKind::MissingSeparator => {
+                write!(f, "expected <lo>-<hi>, found {:?}", self.text)
+            }
+            RangeErrorKind::InvalidBound => write!(f, "invalid bound {:?}", self.text),
+            RangeErrorKind::Reversed => write!(f, "range {:?} ends before it starts", self.text),
+            RangeErrorKind::WrongCount => write!(f, "expected two ranges, found {:?}", self.text),
+        }
+    }
+}
+
+impl Error for RangeError {}
+
+impl RangeError {
+    pub fn new(line: usize, text: &str, kind: RangeErrorKind) -> Self {
+        RangeError {
+            line,
+            text: String::from(text),
+            kind,
+        }
+    }
+}
+
+/// An inclusive range `lo..=hi` with `lo <= hi`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Range<T = i32> {
+    pub lo: T,
+    pub hi: T,
+}
+
+impl<T: Ord + Copy> Range<T> {
+    pub fn new(lo: T, hi: T) -> Self {
+        assert!(lo <= hi, "Range must not end before it starts.");
+        Range { lo, hi }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.lo <= value && value <= self.hi
+    }
+
+    pub fn is_contained_in(&self, other: &Range<T>) -> bool {
+        (self.lo >= other.lo) && (self.hi <= other.hi)
+    }
+
+    pub fn is_overlapping(&self, other: &Range<T>) -> bool {
+        self.lo <= other.hi && other.lo <= self.hi
+    }
+
+    pub fn intersection(&self, other: &Range<T>) -> Option<Range<T>> {
+        if !self.is_overlapping(other) {
+            return None;
+        }
+        Some(Range {
+            lo: self.lo.max(other.lo),
+            hi: self.hi.min(other.hi),
+        })
+    }
+}
+
+impl<T: Discrete> Range<T> {
+    /// Number of values in the range.
+    pub fn len(&self) -> u64 {
+        T::count(self.lo, self.hi)
+    }
+
+    /// Never true: a range holds at least its low bound.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Whether `other` starts right after this range ends, or the other way
+    /// around.
+    pub fn is_adjacent(&self, other: &Range<T>) -> bool {
+        self.hi.succ() == Some(other.lo) || other.hi.succ() == Some(self.lo)
+    }
+
+    /// The single range covering both, if they overlap or touch.
+    pub fn union(&self, other: &Range<T>) -> Option<Range<T>> {
+        if !self.is_overlapping(other) && !self.is_adjacent(other) {
+            return None;
+        }
+        Some(Range {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        })
+    }
+
+    /// The parts of this range not in `other`, in order: none, one or two
+    /// ranges.
+    pub fn difference(&self, other: &Range<T>) -> Vec<Range<T>> {
+        if !self.is_overlapping(other) {
+            return vec![*self];
+        }
+
+        let mut parts = vec![];
+        if self.lo < other.lo {
+            if let Some(hi) = other.lo.pred() {
+                parts.push(Range { lo: self.lo, hi });
+            }
+        }
+        if other.hi < self.hi {
+            if let Some(lo) = other.hi.succ() {
+                parts.push(Range { lo, hi: self.hi });
+            }
+        }
+        parts
+    }
+}
+
+impl<T: FromStr + Ord + Copy> Range<T> {
+    /// Parse `<lo>-<hi>`. A leading `-` on either bound is read as a sign.
+    pub fn parse(lo_hi: &str) -> Result<Range<T>, RangeError> {
+        let err = |kind| RangeError::new(0, lo_hi, kind);
+
+        let split = lo_hi
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '-')
+            .map(|(idx, _)| idx)
+            .ok_or_else(|| err(RangeErrorKind::MissingSeparator))?;
+        let (lo, hi) = (&lo_hi[..split], &lo_hi[split + 1..]);
+
+        let bound = |text: &str| {
+            text.trim()
+                .parse::<T>()
+                .map_err(|_| RangeError::new(0, text, RangeErrorKind::InvalidBound))
+        };
+        let (lo, hi) = (bound(lo)?, bound(hi)?);
+        if hi < lo {
+            return Err(err(RangeErrorKind::Reversed));
+        }
+        Ok(Range { lo, hi })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Range<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.lo, self.hi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_ops() {
+        let a = Range::new(2, 6);
+        let b = Range::new(5, 9);
+
+        assert_eq!(a.intersection(&b), Some(Range::new(5, 6)));
+        assert_eq!(a.intersection(&Range::new(7, 9)), None);
+        assert_eq!(a.union(&b), Some(Range::new(2, 9)));
+        assert_eq!(a.union(&Range::new(7, 9)), Some(Range::new(2, 9)));
+        assert_eq!(a.union(&Range::new(8, 9)), None);
+        assert_eq!(a.len(), 5);
+        assert!(Range::new(1, 10).is_overlapping(&Range::new(3, 4)));
+    }
+
+    #[test]
+    fn test_range_difference() {
+        let a = Range::new(2u8, 9);
+        assert_eq!(
+            a.difference(&Range::new(4, 5)),
+            vec![Range::new(2, 3), Range::new(6, 9)]
+        );
+        assert_eq!(a.difference(&Range::new(0, 3)), vec![Range::new(4, 9)]);
+        assert_eq!(a.difference(&Range::new(0, 255)), vec![]);
+        assert_eq!(a.difference(&Range::new(10, 12)), vec![a]);
+        assert_eq!(Range::new(0u8, 255).len(), 256);
+    }
+
+    #[test]
+    fn test_range_parse() {
+        assert_eq!(Range::<i64>::parse("-5--3").unwrap(), Range::new(-5, -3));
+        assert!(matches!(
+            Range::<i32>::parse("7").unwrap_err().kind,
+            RangeErrorKind::MissingSeparator
+        ));
+        assert!(matches!(
+            Range::<i32>::parse("3-x").unwrap_err().kind,
+            RangeErrorKind::InvalidBound
+        ));
+        assert!(matches!(
+            Range::<u32>::parse("5-3").unwrap_err().kind,
+            RangeErrorKind::Reversed
+        ));
+    }
+}
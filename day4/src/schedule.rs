@@ -0,0 +1,82 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::coverage::{assignments, Assignment};
+use crate::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    pub num_shifts: usize,
+    /// The shift of each elf, per input pair.
+    pub shifts: Vec<[usize; 2]>,
+}
+
+impl Schedule {
+    pub fn shift(&self, pair: usize, elf: usize) -> usize {
+        self.shifts[pair][elf]
+    }
+
+    /// The assignments working `shift`, ordered by start.
+    pub fn members(&self, pairs: &[(Range, Range)], shift: usize) -> Vec<Assignment> {
+        let mut members: Vec<Assignment> = assignments(pairs)
+            .into_iter()
+            .filter(|a| self.shift(a.pair, a.elf) == shift)
+            .collect();
+        members.sort_by_key(|a| a.range);
+        members
+    }
+}
+
+/// Put every assignment in a shift so that no two in the same shift
+/// overlap, using as few shifts as possible.
+///
+/// Assignments are handed out in order of start, each taking the
+/// lowest-numbered shift that is free by then. For intervals this greedy
+/// colouring is optimal: it needs exactly as many shifts as the most elves
+/// ever working one section. Runs in O(n log n).
+pub fn schedule_shifts(pairs: &[(Range, Range)]) -> Schedule {
+    let mut elves = assignments(pairs);
+    elves.sort_by_key(|a| (a.range.lo, a.pair, a.elf));
+
+    let mut shifts = vec![[0; 2]; pairs.len()];
+    let mut busy: BinaryHeap<Reverse<(i32, usize)>> = BinaryHeap::new();
+    let mut free: BinaryHeap<Reverse<usize>> = BinaryHeap::new();
+    let mut num_shifts = 0;
+
+    for a in elves.iter() {
+        while let Some(&Reverse((end, shift))) = busy.peek() {
+            if end >= a.range.lo {
+                break;
+            }
+            busy.pop();
+            free.push(Reverse(shift));
+        }
+
+        let shift = match free.pop() {
+            Some(Reverse(shift)) => shift,
+            None => {
+                num_shifts += 1;
+                num_shifts - 1
+            }
+        };
+        shifts[a.pair][a.elf] = shift;
+        busy.push(Reverse((a.range.hi, shift)));
+    }
+
+    Schedule { num_shifts, shifts }
+}
+
+/// As many assignments as possible with no two overlapping, ordered by
+/// start. Picks whichever compatible assignment ends first, in O(n log n).
+pub fn max_disjoint(pairs: &[(Range, Range)]) -> Vec<Assignment> {
+    let mut elves = assignments(pairs);
+    elves.sort_by_key(|a| (a.range.hi, a.range.lo, a.pair, a.elf));
+
+    let mut chosen: Vec<Assignment> = vec![];
+    for a in elves {
+        if chosen.last().is_none_or(|last| last.range.hi < a.range.lo) {
+            chosen.push(a);
+        }
+    }
+    chosen
+}
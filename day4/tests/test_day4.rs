@@ -1,18 +1,22 @@
 #[cfg(test)]
 mod tests_day4 {
 
+    use day4::coverage::CoverageReport;
+    use day4::interval_tree::IntervalTree;
+    use day4::range::RangeErrorKind;
+    use day4::schedule::{max_disjoint, schedule_shifts};
     use day4::*;
 
     #[test]
     fn test_range_parse() {
-        let range = Range::parse("2-4");
+        let range: Range = Range::parse("2-4").unwrap();
         assert_eq!(range.lo, 2);
         assert_eq!(range.hi, 4);
     }
 
     #[test]
     fn test_range_file_parse() {
-        let ranges = parse_range_file("input_test.txt");
+        let ranges = parse_range_file("input_test.txt").unwrap();
 
         assert_eq!(ranges[0].0.lo, 2);
         assert_eq!(ranges[0].0.hi, 4);
@@ -27,15 +31,114 @@ mod tests_day4 {
 
     #[test]
     fn test_pt1() {
-        let ranges = parse_range_file("input_test.txt");
+        let ranges = parse_range_file("input_test.txt").unwrap();
         let num_contained = count_contained_ranges(&ranges);
         assert_eq!(num_contained, 2);
     }
 
     #[test]
     fn test_pt2() {
-        let ranges = parse_range_file("input_test.txt");
+        let ranges = parse_range_file("input_test.txt").unwrap();
         let num_contained = count_overlapping_ranges(&ranges);
         assert_eq!(num_contained, 4);
     }
+
+    #[test]
+    fn test_range_file_errors() {
+        let err = read_ranges("2-4,6-8\n\n2-3;4-5\n".as_bytes()).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(matches!(err.kind, RangeErrorKind::WrongCount));
+
+        let err = read_ranges("2-4,6-8\n8-6,1-2\n".as_bytes()).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(matches!(err.kind, RangeErrorKind::Reversed));
+        assert_eq!(
+            err.to_string(),
+            "line 2: range \"8-6\" ends before it starts"
+        );
+    }
+
+    #[test]
+    fn test_interval_set_of_pairs() {
+        let ranges = parse_range_file("input_test.txt").unwrap();
+        let covered: IntervalSet = ranges.iter().flat_map(|&(a, b)| [a, b]).collect();
+        assert_eq!(covered.iter().collect::<Vec<_>>(), vec![&Range::new(2, 9)]);
+        assert_eq!(covered.size(), 8);
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        let report = CoverageReport::new(&parse_range_file("input_test.txt").unwrap());
+        assert!(report.uncovered.is_empty());
+        assert_eq!(report.max_depth, 8);
+        assert_eq!(report.max_depth_at, vec![Range::new(6, 6)]);
+        assert_eq!(report.histogram, vec![0, 1, 0, 0, 2, 1, 1, 2, 1]);
+        assert_eq!(report.redundant.len(), 11);
+        assert!(!report.redundant.iter().any(|a| a.range == Range::new(7, 9)));
+    }
+
+    #[test]
+    fn test_coverage_gaps() {
+        let pairs = read_ranges("1-3,2-4\n10-12,11-11\n".as_bytes()).unwrap();
+        let report = CoverageReport::new(&pairs);
+        assert_eq!(report.uncovered, vec![Range::new(5, 9)]);
+        assert_eq!(report.histogram, vec![5, 4, 3]);
+        assert_eq!(
+            report.max_depth_at,
+            vec![Range::new(2, 3), Range::new(11, 11)]
+        );
+        let redundant: Vec<(usize, usize)> =
+            report.redundant.iter().map(|a| (a.pair, a.elf)).collect();
+        assert_eq!(redundant, vec![(1, 1)]);
+        assert_eq!(report.to_csv().lines().nth(4), Some("5,9,5,0"));
+    }
+
+    #[test]
+    fn test_interval_tree_lookups() {
+        let mut tree = IntervalTree::from_pairs(&parse_range_file("input_test.txt").unwrap());
+        assert_eq!(tree.len(), 12);
+
+        let mut covering: Vec<(usize, usize)> =
+            tree.covering(9).iter().map(|a| (a.pair, a.elf)).collect();
+        assert_eq!(covering, vec![(2, 1)]);
+
+        let first = tree.covering(2)[0];
+        assert!(tree.remove(&first));
+        covering = tree.covering(2).iter().map(|a| (a.pair, a.elf)).collect();
+        covering.sort();
+        assert_eq!(covering.len(), 3);
+        assert!(!covering.contains(&(first.pair, first.elf)));
+
+        assert_eq!(tree.overlapping(&Range::new(8, 20)).len(), 4);
+    }
+
+    #[test]
+    fn test_schedule_shifts() {
+        let ranges = parse_range_file("input_test.txt").unwrap();
+        let schedule = schedule_shifts(&ranges);
+        assert_eq!(schedule.num_shifts, CoverageReport::new(&ranges).max_depth);
+
+        for shift in 0..schedule.num_shifts {
+            let members = schedule.members(&ranges, shift);
+            assert!(!members.is_empty());
+            assert!(members
+                .windows(2)
+                .all(|w| !w[0].range.is_overlapping(&w[1].range)));
+        }
+    }
+
+    #[test]
+    fn test_max_disjoint() {
+        let ranges = parse_range_file("input_test.txt").unwrap();
+        let chosen: Vec<Range> = max_disjoint(&ranges).iter().map(|a| a.range).collect();
+        assert_eq!(
+            chosen,
+            vec![
+                Range::new(2, 3),
+                Range::new(4, 5),
+                Range::new(6, 6),
+                Range::new(7, 9)
+            ]
+        );
+    }
 }
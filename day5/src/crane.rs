@@ -0,0 +1,169 @@
+use crate::error::ExecErrorKind;
+use crate::{ShipState, Step};
+
+/// A crane model: how one step turns into primitive crane operations.
+///
+/// Cranes move crates only through [`ShipState::lift`] and
+/// [`ShipState::lift_bottom`], so a crane defined outside this crate works
+/// the same way as the built-in ones.
+pub trait Crane {
+    fn name(&self) -> String;
+
+    /// Carry out `step` on `ship` and return how many primitive operations
+    /// it took. A step the ship can't carry out is rejected before anything
+    /// moves.
+    fn apply(&self, ship: &mut ShipState, step: &Step) -> Result<usize, ExecErrorKind>;
+}
+
+/// Lifts one crate at a time, so moved crates end up in reverse order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9000")
+    }
+
+    fn apply(&self, ship: &mut ShipState, step: &Step) -> Result<usize, ExecErrorKind> {
+        ship.check(step)?;
+        for _ in 0..step.count {
+            ship.lift(1, step.from, step.to)?;
+        }
+        Ok(step.count as usize)
+    }
+}
+
+/// Lifts any number of crates at once, keeping their order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9001")
+    }
+
+    fn apply(&self, ship: &mut ShipState, step: &Step) -> Result<usize, ExecErrorKind> {
+        ship.check(step)?;
+        if step.count == 0 {
+            return Ok(0);
+        }
+        ship.lift(step.count as usize, step.from, step.to)?;
+        Ok(1)
+    }
+}
+
+/// Lifts at most `max` crates at once, keeping their order. Bigger moves
+/// are split into loads of `max`, taken from the top.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxLiftCrane {
+    max: usize,
+}
+
+impl MaxLiftCrane {
+    /// A crane lifting up to `max` crates, or `None` if `max` is 0.
+    pub fn new(max: usize) -> Option<Self> {
+        (max > 0).then_some(MaxLiftCrane { max })
+    }
+}
+
+impl Crane for MaxLiftCrane {
+    fn name(&self) -> String {
+        format!("max-lift {}", self.max)
+    }
+
+    fn apply(&self, ship: &mut ShipState, step: &Step) -> Result<usize, ExecErrorKind> {
+        ship.check(step)?;
+        let mut remaining = step.count as usize;
+        let mut ops = 0;
+        while remaining > 0 {
+            let load = remaining.min(self.max);
+            ship.lift(load, step.from, step.to)?;
+            remaining -= load;
+            ops += 1;
+        }
+        Ok(ops)
+    }
+}
+
+/// Slides crates out from the bottom of the source stack one at a time and
+/// drops each on top of the destination.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BottomFirstCrane;
+
+impl Crane for BottomFirstCrane {
+    fn name(&self) -> String {
+        String::from("bottom-first")
+    }
+
+    fn apply(&self, ship: &mut ShipState, step: &Step) -> Result<usize, ExecErrorKind> {
+        ship.check(step)?;
+        for _ in 0..step.count {
+            ship.lift_bottom(step.from, step.to)?;
+        }
+        Ok(step.count as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship() -> ShipState {
+        ShipState::from_path("input_test.txt").unwrap()
+    }
+
+    #[test]
+    fn test_max_lift() {
+        let step = Step {
+            count: 3,
+            from: 2,
+            to: 1,
+        };
+        let mut limited = ship();
+        let mut together = ship();
+        assert_eq!(
+            MaxLiftCrane::new(2).unwrap().apply(&mut limited, &step),
+            Ok(2)
+        );
+        assert_eq!(CrateMover9001.apply(&mut together, &step), Ok(1));
+        assert_eq!(limited.top_stacks(), "M P");
+        assert_eq!(together.top_stacks(), "D P");
+    }
+
+    #[test]
+    fn test_bottom_first() {
+        let mut ship = ship();
+        let step = Step {
+            count: 2,
+            from: 1,
+            to: 3,
+        };
+        assert_eq!(BottomFirstCrane.apply(&mut ship, &step), Ok(2));
+        assert_eq!(ship.top_stacks(), " DN");
+    }
+
+    #[test]
+    fn test_rejects_bad_steps() {
+        let mut empty = ShipState::new(2);
+        let cranes: [&dyn Crane; 4] = [
+            &CrateMover9000,
+            &CrateMover9001,
+            &MaxLiftCrane::new(2).unwrap(),
+            &BottomFirstCrane,
+        ];
+        for crane in cranes {
+            assert_eq!(
+                crane.apply(&mut empty, &Step::new(1, 0, 1)),
+                Err(ExecErrorKind::InvalidStack { stack: 0 })
+            );
+            assert_eq!(
+                crane.apply(&mut empty, &Step::new(1, 1, 3)),
+                Err(ExecErrorKind::InvalidStack { stack: 3 })
+            );
+
+            let mut ship = ship();
+            assert!(crane.apply(&mut ship, &Step::new(3, 1, 2)).is_err());
+            assert_eq!(ship.top_stacks(), "NDP");
+        }
+    }
+}
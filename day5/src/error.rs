@@ -0,0 +1,87 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecErrorKind {
+    /// Stacks are numbered from 1 to the number of stacks.
+    InvalidStack { stack: u32 },
+    NotEnoughCrates {
+        stack: u32,
+        needed: u32,
+        available: usize,
+    },
+}
+
+impl fmt::Display for ExecErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecErrorKind::InvalidStack { stack } => write!(f, "no stack {}", stack),
+            ExecErrorKind::NotEnoughCrates {
+                stack,
+                needed,
+                available,
+            } => write!(
+                f,
+                "stack {} holds {} crates, {} needed",
+                stack, available, needed
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecError {
+    /// Position of the failing step in the list, counting from 0. Displayed
+    /// counting from 1.
+    pub step: usize,
+    pub kind: ExecErrorKind,
+}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "step {}: {}", self.step + 1, self.kind)
+    }
+}
+
+impl Error for ExecError {}
+
+impl Error for ExecErrorKind {}
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    /// A line in the procedure that isn't a `move` step.
+    UnrecognisedLine {
+        line: usize,
+        text: String,
+    },
+    /// A crate in the drawing that isn't over a stack number, or shares one
+    /// with another crate.
+    MisplacedCrate {
+        label: String,
+        text: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "I/O error: {}", e),
+            ParseError::UnrecognisedLine { line, text } => {
+                write!(f, "line {}: unrecognised step {:?}", line, text)
+            }
+            ParseError::MisplacedCrate { label, text } => {
+                write!(f, "crate [{}] in {:?} isn't over one stack", label, text)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
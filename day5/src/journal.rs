@@ -0,0 +1,298 @@
+use crate::crane::Crane;
+use crate::error::{ExecError, ExecErrorKind};
+use crate::{Crate, ShipState, Step};
+
+/// One applied step. Alongside the crates it moved, the entry keeps the
+/// ship from before and after the step, so undoing or redoing it just puts
+/// that ship back, whichever stacks the crane touched.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub step: Step,
+    /// The moved crates as they ended up on the destination, top first.
+    pub moved: Vec<Crate>,
+    /// Primitive operations the crane took.
+    pub ops: usize,
+    before: ShipState,
+    after: ShipState,
+}
+
+/// A ship together with the history of steps applied to it. Undo, redo and
+/// jumps never re-run a crane, they put a recorded ship back.
+///
+/// Recording a step isn't free: the entry holds one pointer per stack from
+/// before the step, so the crane copies each stack it touches the first time
+/// it writes. Every journaled step costs O(stacks + stack height) time and
+/// memory.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    ship: ShipState,
+    entries: Vec<Entry>,
+    /// Number of entries currently applied to `ship`.
+    position: usize,
+}
+
+impl Journal {
+    pub fn new(ship: ShipState) -> Journal {
+        Journal {
+            ship,
+            entries: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// The ship after the first `position()` steps.
+    pub fn ship(&self) -> &ShipState {
+        &self.ship
+    }
+
+    pub fn into_ship(self) -> ShipState {
+        self.ship
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// A copy of the ship as it is now. Stacks are shared with the journal,
+    /// so this costs one pointer per stack.
+    pub fn snapshot(&self) -> ShipState {
+        self.ship.clone()
+    }
+
+    /// Run `step` with `crane` and record it. Any undone entries are
+    /// discarded, as in an editor.
+    pub fn apply<C: Crane + ?Sized>(
+        &mut self,
+        crane: &C,
+        step: &Step,
+    ) -> Result<usize, ExecErrorKind> {
+        self.ship.check(step)?;
+
+        let before = self.ship.clone();
+        let ops = crane.apply(&mut self.ship, step).inspect_err(|_| {
+            // Put back anything a crane moved before it gave up
+            self.ship = before.clone();
+        })?;
+        let after = self.ship.clone();
+        let moved = after.stacks[step.to as usize - 1]
+            .iter()
+            .take(step.count as usize)
+            .cloned()
+            .collect();
+
+        self.entries.truncate(self.position);
+        self.entries.push(Entry {
+            step: *step,
+            moved,
+            ops,
+            before,
+            after,
+        });
+        self.position += 1;
+        Ok(ops)
+    }
+
+    /// Run and record every step with `crane`, returning the number of
+    /// primitive operations. Every step is checked first, so on error
+    /// nothing is run or recorded.
+    pub fn execute<C: Crane + ?Sized>(
+        &mut self,
+        crane: &C,
+        steps: &[Step],
+    ) -> Result<usize, ExecError> {
+        if let Some(e) = self.ship.validate(steps).into_iter().next() {
+            return Err(e);
+        }
+        steps
+            .iter()
+            .enumerate()
+            .map(|(idx, step)| {
+                self.apply(crane, step)
+                    .map_err(|kind| ExecError { step: idx, kind })
+            })
+            .sum()
+    }
+
+    /// Take back the last applied step, returning its entry.
+    pub fn undo(&mut self) -> Option<&Entry> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        let entry = &self.entries[self.position];
+        self.ship = entry.before.clone();
+        Some(entry)
+    }
+
+    /// Re-apply the next undone step, returning its entry.
+    pub fn redo(&mut self) -> Option<&Entry> {
+        let entry = self.entries.get(self.position)?;
+        self.ship = entry.after.clone();
+        self.position += 1;
+        Some(entry)
+    }
+
+    /// Undo or redo until the first `position` steps are applied.
+    ///
+    /// Panics if `position` is past the end of the journal.
+    pub fn seek(&mut self, position: usize) {
+        assert!(
+            position <= self.entries.len(),
+            "Position {} is past the {} recorded steps.",
+            position,
+            self.entries.len()
+        );
+        while self.position > position {
+            self.undo();
+        }
+        while self.position < position {
+            self.redo();
+        }
+    }
+
+    /// The ship after the first `position` steps, leaving the journal where
+    /// it is. This copies one recorded ship, a pointer per stack.
+    ///
+    /// Panics if `position` is past the end of the journal.
+    pub fn snapshot_at(&self, position: usize) -> ShipState {
+        assert!(
+            position <= self.entries.len(),
+            "Position {} is past the {} recorded steps.",
+            position,
+            self.entries.len()
+        );
+        if position == self.position {
+            self.ship.clone()
+        } else if position == 0 {
+            self.entries[0].before.clone()
+        } else {
+            self.entries[position - 1].after.clone()
+        }
+    }
+}
+
+impl From<ShipState> for Journal {
+    fn from(ship: ShipState) -> Self {
+        Journal::new(ship)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{BottomFirstCrane, CrateMover9000};
+    use std::sync::Arc;
+
+    /// Lifts the crates one at a time onto `buffer`, then slides as many out
+    /// from under it onto the destination, so the buffer's own crates move
+    /// too. Fails after lifting if `to` is `fail_to`.
+    struct BufferCrane {
+        buffer: u32,
+        fail_to: u32,
+    }
+
+    impl Crane for BufferCrane {
+        fn name(&self) -> String {
+            String::from("buffer")
+        }
+
+        fn apply(&self, ship: &mut ShipState, step: &Step) -> Result<usize, ExecErrorKind> {
+            for _ in 0..step.count {
+                ship.lift(1, step.from, self.buffer)?;
+            }
+            if step.to == self.fail_to {
+                ship.lift_bottom(self.fail_to + 10, step.to)?;
+            }
+            for _ in 0..step.count {
+                ship.lift_bottom(self.buffer, step.to)?;
+            }
+            Ok(2 * step.count as usize)
+        }
+    }
+
+    fn journal() -> Journal {
+        Journal::new(ShipState::from_path("input_test.txt").unwrap())
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut journal = journal();
+        journal.apply(&CrateMover9000, &Step::new(2, 2, 3)).unwrap();
+        journal
+            .apply(&BottomFirstCrane, &Step::new(1, 1, 2))
+            .unwrap();
+        assert_eq!(journal.ship().top_stacks(), "NZC");
+
+        let entry = journal.undo().unwrap();
+        assert_eq!(entry.moved, vec![Crate::new("Z")]);
+        assert_eq!(journal.ship().top_stacks(), "NMC");
+        journal.undo();
+        assert_eq!(journal.ship().top_stacks(), "NDP");
+        assert!(journal.undo().is_none());
+
+        assert_eq!(journal.redo().unwrap().moved.len(), 2);
+        assert_eq!(journal.ship().top_stacks(), "NMC");
+
+        // A new step drops the undone one
+        journal.apply(&CrateMover9000, &Step::new(1, 1, 3)).unwrap();
+        assert_eq!(journal.len(), 2);
+        assert!(journal.redo().is_none());
+        assert_eq!(journal.ship().top_stacks(), "ZMN");
+    }
+
+    #[test]
+    fn test_snapshot_shares_stacks() {
+        let mut journal = journal();
+        let before = journal.snapshot();
+        journal.apply(&CrateMover9000, &Step::new(1, 1, 2)).unwrap();
+
+        assert!(Arc::ptr_eq(&before.stacks[2], &journal.ship().stacks[2]));
+        assert!(!Arc::ptr_eq(&before.stacks[0], &journal.ship().stacks[0]));
+        assert_eq!(before.top_stacks(), "NDP");
+        assert_eq!(journal.snapshot_at(0), before);
+        assert_eq!(journal.position(), 1);
+
+        let after = journal.snapshot();
+        journal.apply(&CrateMover9000, &Step::new(2, 2, 3)).unwrap();
+        journal.seek(1);
+        assert_eq!(journal.snapshot_at(2).top_stacks(), "ZCD");
+        assert_eq!(journal.snapshot_at(1), after);
+        assert_eq!(journal.ship(), &after);
+    }
+
+    #[test]
+    fn test_undo_third_stack() {
+        let mut journal = journal();
+        let start = journal.snapshot();
+        let crane = BufferCrane {
+            buffer: 3,
+            fail_to: 2,
+        };
+        assert_eq!(journal.apply(&crane, &Step::new(2, 2, 1)), Ok(4));
+        assert_eq!(journal.ship().top_stacks(), "DMC");
+        let after = journal.snapshot();
+
+        journal.undo();
+        assert_eq!(journal.ship(), &start);
+        journal.redo();
+        assert_eq!(journal.ship(), &after);
+        assert_eq!(journal.snapshot_at(0), start);
+
+        // A crane giving up halfway leaves nothing moved or recorded
+        assert!(journal.apply(&crane, &Step::new(1, 1, 2)).is_err());
+        assert_eq!(journal.ship(), &after);
+        assert_eq!(journal.len(), 1);
+    }
+}
@@ -1,18 +1,32 @@
 #![allow(unused, unused_imports)]
 
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 use std::primitive::char;
 use std::{fmt, vec};
 
 use std::collections::VecDeque;
+use std::iter::zip;
+use std::sync::Arc;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-#[derive(Debug, Clone)]
+pub mod crane;
+pub mod error;
+pub mod journal;
+
+use crane::{Crane, CrateMover9000, CrateMover9001};
+use error::{ExecError, ExecErrorKind, ParseError};
+
+lazy_static! {
+    static ref RE_CRATE: Regex = Regex::new(r"\[([A-Za-z0-9]+)\]").unwrap();
+    static ref RE_STACK_NUM: Regex = Regex::new(r"\d+").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Crate {
-    pub id: char,
+    pub id: String,
 }
 
 impl fmt::Display for Crate {
@@ -22,164 +36,327 @@ impl fmt::Display for Crate {
 }
 
 impl Crate {
-    pub fn parse(inp: &str) -> Option<Crate> {
-        lazy_static! {
-            static ref RE_CRATE: Regex = Regex::new(r"\[([A-Z])\]").unwrap();
-        }
+    pub fn new(id: &str) -> Crate {
+        Crate { id: id.to_string() }
+    }
 
+    pub fn parse(inp: &str) -> Option<Crate> {
         if RE_CRATE.captures_len() != 2 {
             panic!("Captured multiple crates in parse()");
         }
 
         let caps = RE_CRATE.captures(inp);
         match caps {
-            Some(_) => Some(Crate {
-                id: caps?.get(1).unwrap().as_str().chars().collect::<Vec<_>>()[0],
-            }),
+            Some(_) => Some(Crate::new(caps?.get(1).unwrap().as_str())),
             _ => None,
         }
     }
 }
 
+/// Stacks are shared copy-on-write, so cloning a ship is cheap and only the
+/// stacks a step touches get copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ShipState {
-    stacks: Vec<VecDeque<Crate>>,
+    stacks: Vec<Arc<VecDeque<Crate>>>,
 }
 
 impl ShipState {
     pub fn new(num_stacks: u32) -> ShipState {
         let mut stacks = Vec::new();
         for idx in 0..num_stacks {
-            stacks.push(VecDeque::new())
+            stacks.push(Arc::new(VecDeque::new()))
         }
         ShipState { stacks }
     }
 
-    pub fn move_crates(&mut self, count: u32, from_stack: u32, to_stack: u32) {
-        // Switch from 1-indexing to 0-indexing
-        let from_stack_idx: usize = (from_stack - 1).try_into().unwrap();
-        let to_stack_idx: usize = (to_stack - 1).try_into().unwrap();
+    fn heights(&self) -> Vec<usize> {
+        self.stacks.iter().map(|s| s.len()).collect()
+    }
 
-        if from_stack_idx >= self.stacks.len() {
-            panic!("Invalid source stack.");
-        }
-        if to_stack_idx >= self.stacks.len() {
-            panic!("Invalid destination stack.");
+    /// Check `step` against the stack `heights`, and update them as if it
+    /// ran.
+    fn check_step(heights: &mut [usize], step: &Step) -> Result<(), ExecErrorKind> {
+        for stack in [step.from, step.to] {
+            if stack == 0 || stack as usize > heights.len() {
+                return Err(ExecErrorKind::InvalidStack { stack });
+            }
         }
 
-        if self.stacks[from_stack_idx].len() < count.try_into().unwrap() {
-            panic!("Stack does not hold enough crates to move.");
+        let (from, to) = (step.from as usize - 1, step.to as usize - 1);
+        if heights[from] < step.count as usize {
+            return Err(ExecErrorKind::NotEnoughCrates {
+                stack: step.from,
+                needed: step.count,
+                available: heights[from],
+            });
         }
+        heights[from] -= step.count as usize;
+        heights[to] += step.count as usize;
+        Ok(())
+    }
 
-        for c in 0..count {
-            let popped = self.stacks[from_stack_idx].pop_front().unwrap();
-            self.stacks[to_stack_idx].push_front(popped);
-        }
+    /// Check that `step` can be carried out on the ship as it is.
+    pub fn check(&self, step: &Step) -> Result<(), ExecErrorKind> {
+        ShipState::check_step(&mut self.heights(), step)
     }
 
-    pub fn move_crates_together(&mut self, count: u32, from_stack: u32, to_stack: u32) {
-        // Switch from 1-indexing to 0-indexing
-        let from_stack_idx: usize = (from_stack - 1).try_into().unwrap();
-        let to_stack_idx: usize = (to_stack - 1).try_into().unwrap();
+    /// Dry run of `steps`, reporting every step that could not be carried
+    /// out. Failing steps are skipped, so later steps are checked against
+    /// the ship as it would be without them.
+    pub fn validate(&self, steps: &[Step]) -> Vec<ExecError> {
+        let mut heights = self.heights();
+        steps
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, step)| {
+                ShipState::check_step(&mut heights, step)
+                    .err()
+                    .map(|kind| ExecError { step: idx, kind })
+            })
+            .collect()
+    }
 
-        if from_stack_idx >= self.stacks.len() {
-            panic!("Invalid source stack.");
+    /// Index into `stacks` of the 1-based `stack`.
+    fn stack_index(&self, stack: u32) -> Result<usize, ExecErrorKind> {
+        if stack == 0 || stack as usize > self.stacks.len() {
+            return Err(ExecErrorKind::InvalidStack { stack });
         }
-        if to_stack_idx >= self.stacks.len() {
-            panic!("Invalid destination stack.");
+        Ok(stack as usize - 1)
+    }
+
+    /// Index into `stacks` of the 1-based `stack`, checking it holds at
+    /// least `count` crates.
+    fn source_index(&self, stack: u32, count: usize) -> Result<usize, ExecErrorKind> {
+        let idx = self.stack_index(stack)?;
+        if self.stacks[idx].len() < count {
+            return Err(ExecErrorKind::NotEnoughCrates {
+                stack,
+                needed: count as u32,
+                available: self.stacks[idx].len(),
+            });
         }
+        Ok(idx)
+    }
 
-        if self.stacks[from_stack_idx].len() < count.try_into().unwrap() {
-            panic!("Stack does not hold enough crates to move.");
+    /// Primitive operation: lift the top `count` crates of stack `from` and
+    /// set them down on stack `to` in the same order. Cranes are built from
+    /// this and [`ShipState::lift_bottom`]; nothing moves if either stack
+    /// doesn't exist or `from` holds fewer than `count` crates.
+    pub fn lift(&mut self, count: usize, from: u32, to: u32) -> Result<(), ExecErrorKind> {
+        let to = self.stack_index(to)?;
+        let from = self.source_index(from, count)?;
+        let lifted: Vec<Crate> = Arc::make_mut(&mut self.stacks[from])
+            .drain(..count)
+            .collect();
+        let dest = Arc::make_mut(&mut self.stacks[to]);
+        for c in lifted.into_iter().rev() {
+            dest.push_front(c);
         }
+        Ok(())
+    }
 
-        let mut collect_popped = Vec::with_capacity(count.try_into().unwrap());
+    /// Primitive operation: slide the bottom crate of stack `from` out and
+    /// set it down on top of stack `to`. Nothing moves if either stack
+    /// doesn't exist or `from` is empty.
+    pub fn lift_bottom(&mut self, from: u32, to: u32) -> Result<(), ExecErrorKind> {
+        let to = self.stack_index(to)?;
+        let from = self.source_index(from, 1)?;
+        let c = Arc::make_mut(&mut self.stacks[from]).pop_back().unwrap();
+        Arc::make_mut(&mut self.stacks[to]).push_front(c);
+        Ok(())
+    }
 
-        for c in 0..count {
-            let popped = self.stacks[from_stack_idx].pop_front().unwrap();
-            collect_popped.push(popped);
-        }
+    pub fn move_crates(
+        &mut self,
+        count: u32,
+        from_stack: u32,
+        to_stack: u32,
+    ) -> Result<(), ExecErrorKind> {
+        self.apply(&CrateMover9000, &Step::new(count, from_stack, to_stack))
+            .map(|_| ())
+    }
 
-        for popped in collect_popped.into_iter().rev() {
-            self.stacks[to_stack_idx].push_front(popped);
-        }
+    pub fn move_crates_together(
+        &mut self,
+        count: u32,
+        from_stack: u32,
+        to_stack: u32,
+    ) -> Result<(), ExecErrorKind> {
+        self.apply(&CrateMover9001, &Step::new(count, from_stack, to_stack))
+            .map(|_| ())
     }
 
-    pub fn parse_crate_line(line: &str) -> Vec<Option<Crate>> {
-        let mut crates = Vec::new();
+    /// Run one step with `crane`, returning the number of primitive
+    /// operations it took.
+    pub fn apply<C: Crane + ?Sized>(
+        &mut self,
+        crane: &C,
+        step: &Step,
+    ) -> Result<usize, ExecErrorKind> {
+        crane.apply(self, step)
+    }
+
+    /// Parse a row of the drawing laid out in the puzzle's fixed 4-character
+    /// columns.
+    pub fn parse_crate_line(line: &str) -> Result<Vec<Option<Crate>>, ParseError> {
+        let width = line.trim_end_matches(['\n', '\r']).len();
+        ShipState::parse_crate_row(line, &ShipState::fixed_columns(width))
+    }
 
-        let mut offset: usize = 0;
-        while offset < line.len() {
-            crates.push(Crate::parse(&line[offset..offset + 3]));
-            offset += 4;
+    /// Parse a row of the drawing, putting each crate in the stack whose
+    /// column is nearest its centre. `columns` are byte offsets in
+    /// increasing order. A crate must reach to within a byte of its column,
+    /// and no two crates may share one.
+    pub fn parse_crate_row(
+        line: &str,
+        columns: &[usize],
+    ) -> Result<Vec<Option<Crate>>, ParseError> {
+        let mut crates = vec![None; columns.len()];
+
+        for caps in RE_CRATE.captures_iter(line) {
+            let span = caps.get(0).unwrap();
+            let label = caps.get(1).unwrap().as_str();
+            let misplaced = || ParseError::MisplacedCrate {
+                label: label.to_string(),
+                text: line.trim_end_matches(['\n', '\r']).to_string(),
+            };
+            if columns.is_empty() {
+                return Err(misplaced());
+            }
+
+            let centre = (span.start() + span.end() - 1) / 2;
+            let right = columns
+                .partition_point(|&c| c < centre)
+                .min(columns.len() - 1);
+            let idx = if right > 0 && centre - columns[right - 1] < columns[right].abs_diff(centre)
+            {
+                right - 1
+            } else {
+                right
+            };
+
+            let near = span.start().saturating_sub(1)..=span.end();
+            if !near.contains(&columns[idx]) || crates[idx].is_some() {
+                return Err(misplaced());
+            }
+            crates[idx] = Some(Crate::new(label));
         }
 
-        crates
+        Ok(crates)
+    }
+
+    /// Column of each stack number in the drawing's footer.
+    pub fn footer_columns(line: &str) -> Vec<usize> {
+        RE_STACK_NUM
+            .find_iter(line)
+            .map(|num| (num.start() + num.end() - 1) / 2)
+            .collect()
+    }
+
+    /// Whether `line` is a drawing's footer: stack numbers and nothing else.
+    fn is_footer(line: &str) -> bool {
+        RE_STACK_NUM.is_match(line)
+            && line
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_whitespace())
     }
 
-    pub fn from_path(path: &str) -> ShipState {
-        let mut reader = BufReader::new(File::open(path).expect("File not found"));
+    fn fixed_columns(width: usize) -> Vec<usize> {
+        (0..width.div_ceil(4)).map(|idx| 4 * idx + 1).collect()
+    }
+
+    pub fn from_path(path: &str) -> Result<ShipState, ParseError> {
+        let mut reader = BufReader::new(File::open(path)?);
         ShipState::from_file(&mut reader)
     }
 
-    pub fn from_file(reader: &mut BufReader<File>) -> ShipState {
-        //let mut reader = BufReader::new(File::open(path).expect("File not found"));
+    /// Read the drawing and the blank line after it. If a non-blank line
+    /// takes the place of the blank one, it is read and dropped; use
+    /// `parse_crate_file` or `parse_crate_file_strict` to keep the steps.
+    pub fn from_file<R: BufRead>(reader: &mut R) -> Result<ShipState, ParseError> {
+        Ok(ShipState::read_drawing(reader)?.0)
+    }
 
+    /// Read the drawing and the blank line after it, returning the ship, how
+    /// many lines belong to the drawing and the first line after it that
+    /// doesn't. That line is empty when the drawing ends with a blank line.
+    fn read_drawing<R: BufRead>(reader: &mut R) -> Result<(ShipState, usize, String), ParseError> {
         let mut line_buf = String::new();
 
-        let mut parsed_stacks: Vec<Vec<Option<Crate>>> = vec![];
-
-        let mut parsing_init = true;
-        while parsing_init {
+        let mut rows: Vec<String> = vec![];
+        loop {
             line_buf.clear();
-            reader
-                .read_line(&mut line_buf)
-                .expect("Could not parse line.");
-
-            let parsed_crates = ShipState::parse_crate_line(&line_buf);
+            reader.read_line(&mut line_buf)?;
 
             // Stop when we reach a line with no crates
-            if !parsed_crates.iter().any(|c| c.is_some()) {
-                parsing_init = false;
+            if !RE_CRATE.is_match(&line_buf) {
                 break;
             }
-            parsed_stacks.push(parsed_crates);
+            rows.push(line_buf.clone());
         }
 
-        // Eat the next line of stack numbers
-        line_buf.clear();
-        reader
-            .read_line(&mut line_buf)
-            .expect("Could not parse line.");
-
-        if parsed_stacks.is_empty() {
-            return ShipState::new(0);
+        // The line that stopped us is normally the footer, holding the stack
+        // numbers that line them up with their columns, and a blank line
+        // follows it. Without a footer the stacks fall back to the fixed
+        // layout.
+        let mut lines_read = rows.len();
+        let columns = if ShipState::is_footer(&line_buf) {
+            lines_read += 1;
+            let columns = ShipState::footer_columns(&line_buf);
+            line_buf.clear();
+            reader.read_line(&mut line_buf)?;
+            columns
+        } else {
+            let width = rows.iter().map(|r| r.trim_end().len()).max().unwrap_or(0);
+            ShipState::fixed_columns(width)
+        };
+
+        // Only a blank line separates the drawing from the procedure; hand
+        // anything else back to the caller.
+        if line_buf.trim().is_empty() {
+            lines_read += 1;
+            line_buf.clear();
         }
 
-        let mut ship = ShipState::new(parsed_stacks[0].len().try_into().unwrap());
-        for parsed_line in parsed_stacks.iter() {
+        let mut ship = ShipState::new(columns.len().try_into().unwrap());
+        for row in rows.iter() {
             // Add any parsed crates to the end of the stack
-            for (stack_idx, parsed) in parsed_line.iter().enumerate() {
+            let parsed_row = ShipState::parse_crate_row(row, &columns)?;
+            for (stack_idx, parsed) in parsed_row.into_iter().enumerate() {
                 if let Some(c) = parsed {
-                    ship.stacks[stack_idx].push_back(c.clone());
+                    Arc::make_mut(&mut ship.stacks[stack_idx]).push_back(c);
                 }
             }
         }
 
-        ship
+        Ok((ship, lines_read, line_buf))
+    }
+
+    /// Put `c` on top of stack `stack`.
+    pub fn push(&mut self, stack: u32, c: Crate) -> Result<(), ExecErrorKind> {
+        let idx = self.stack_index(stack)?;
+        Arc::make_mut(&mut self.stacks[idx]).push_front(c);
+        Ok(())
     }
 
     pub fn num_stacks(&self) -> usize {
         self.stacks.len()
     }
 
+    /// The label on top of each stack, or `None` where a stack is empty.
+    pub fn top_labels(&self) -> Vec<Option<&str>> {
+        self.stacks
+            .iter()
+            .map(|stack| stack.front().map(|c| c.id.as_str()))
+            .collect()
+    }
+
     pub fn top_stacks(&self) -> String {
         let mut top = String::new();
 
-        for stack in self.stacks.iter() {
-            let top_stack = stack.get(0);
-
-            if let Some(c) = top_stack {
-                top.push(c.id)
+        for label in self.top_labels() {
+            if let Some(id) = label {
+                top.push_str(id)
             } else {
                 top.push(' ');
             }
@@ -188,55 +365,171 @@ impl ShipState {
         top
     }
 
-    pub fn execute(&mut self, steps: &[Step]) {
-        for step in steps.iter() {
-            self.move_crates(step.count, step.from, step.to);
+    /// Run every step with `crane`, returning the number of primitive
+    /// operations it took. Every step is checked first, so on error the
+    /// ship is left untouched.
+    pub fn execute<C: Crane + ?Sized>(
+        &mut self,
+        crane: &C,
+        steps: &[Step],
+    ) -> Result<usize, ExecError> {
+        if let Some(e) = self.validate(steps).into_iter().next() {
+            return Err(e);
         }
+        steps
+            .iter()
+            .enumerate()
+            .map(|(idx, step)| {
+                crane
+                    .apply(self, step)
+                    .map_err(|kind| ExecError { step: idx, kind })
+            })
+            .sum()
     }
+}
 
-    pub fn execute_pt2(&mut self, steps: &[Step]) {
-        for step in steps.iter() {
-            self.move_crates_together(step.count, step.from, step.to);
+/// The puzzle's drawing: tallest stack at the top, then a footer numbering
+/// the stacks. Each column is as wide as its widest crate, at least 3
+/// characters, with crates and numbers centred in it.
+impl fmt::Display for ShipState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let widths: Vec<usize> = self
+            .stacks
+            .iter()
+            .enumerate()
+            .map(|(idx, stack)| {
+                let label = stack.iter().map(|c| c.id.len() + 2).max().unwrap_or(0);
+                label.max((idx + 1).to_string().len()).max(3)
+            })
+            .collect();
+
+        let height = self.stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+        for depth in (0..height).rev() {
+            let row: Vec<String> = zip(&self.stacks, &widths)
+                .map(|(stack, &width)| match stack.len().checked_sub(depth + 1) {
+                    Some(idx) => format!("{:^width$}", stack[idx].to_string()),
+                    None => " ".repeat(width),
+                })
+                .collect();
+            writeln!(f, "{}", row.join(" "))?;
         }
+
+        let footer: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(idx, &width)| format!("{:^width$}", idx + 1))
+            .collect();
+        writeln!(f, "{}", footer.join(" "))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Step {
     pub count: u32,
     pub from: u32,
     pub to: u32,
 }
 
-pub fn parse_steps(reader: &mut BufReader<File>) -> Vec<Step> {
+impl Step {
+    pub fn new(count: u32, from: u32, to: u32) -> Step {
+        Step { count, from, to }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.count, self.from, self.to)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Skip any line that isn't a step.
+    Lenient,
+    /// Reject any non-blank line that isn't exactly a step.
+    Strict,
+}
+
+/// Read the procedure from `reader`. `first_line` is the line number of the
+/// first line read, used in errors.
+pub fn read_steps<R: BufRead>(
+    reader: &mut R,
+    mode: ParseMode,
+    first_line: usize,
+) -> Result<Vec<Step>, ParseError> {
     lazy_static! {
-        static ref RE_CRATE: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
+        static ref RE_STEP: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
+        static ref RE_STRICT: Regex = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
     }
+    let re = match mode {
+        ParseMode::Lenient => &*RE_STEP,
+        ParseMode::Strict => &*RE_STRICT,
+    };
 
     let mut steps = Vec::new();
 
-    for line in reader.lines() {
-        if let Some(caps) = RE_CRATE.captures(&line.unwrap()) {
-            let count: u32 = caps.get(1).unwrap().as_str().parse().unwrap();
-            let from: u32 = caps.get(2).unwrap().as_str().parse().unwrap();
-            let to: u32 = caps.get(3).unwrap().as_str().parse().unwrap();
-            steps.push(Step { count, from, to })
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let parsed = re.captures(line.trim()).and_then(|caps| {
+            let num = |i: usize| caps.get(i).unwrap().as_str().parse::<u32>().ok();
+            Some(Step::new(num(1)?, num(2)?, num(3)?))
+        });
+
+        match (parsed, mode) {
+            (Some(step), _) => steps.push(step),
+            (None, ParseMode::Strict) if !line.trim().is_empty() => {
+                return Err(ParseError::UnrecognisedLine {
+                    line: first_line + idx,
+                    text: line,
+                })
+            }
+            (None, _) => {}
         }
     }
 
-    steps
+    Ok(steps)
 }
 
-pub fn parse_crate_file(path: &str) -> (ShipState, Vec<Step>) {
-    let mut reader = BufReader::new(File::open(path).expect("File not found"));
+pub fn parse_steps(reader: &mut BufReader<File>) -> Result<Vec<Step>, ParseError> {
+    read_steps(reader, ParseMode::Lenient, 1)
+}
+
+/// Read a drawing and its procedure, skipping anything that isn't a step.
+pub fn parse_crate_file(path: &str) -> Result<(ShipState, Vec<Step>), ParseError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    read_crate_file(&mut reader, ParseMode::Lenient)
+}
+
+/// Like `parse_crate_file`, but rejects unrecognised step lines instead of
+/// skipping them.
+pub fn parse_crate_file_strict(path: &str) -> Result<(ShipState, Vec<Step>), ParseError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    read_crate_file(&mut reader, ParseMode::Strict)
+}
+
+/// Read a drawing and its procedure, starting the procedure at any line the
+/// drawing handed back.
+fn read_crate_file<R: BufRead>(
+    reader: &mut R,
+    mode: ParseMode,
+) -> Result<(ShipState, Vec<Step>), ParseError> {
+    let (ship, lines_read, rest) = ShipState::read_drawing(reader)?;
+    let steps = read_steps(&mut rest.as_bytes().chain(reader), mode, lines_read + 1)?;
 
-    let ship = ShipState::from_file(&mut reader);
-    let steps = parse_steps(&mut reader);
+    Ok((ship, steps))
+}
 
-    (ship, steps)
+pub fn write_steps<W: Write>(writer: &mut W, steps: &[Step]) -> io::Result<()> {
+    for step in steps {
+        writeln!(writer, "{}", step)?;
+    }
+    Ok(())
 }
 
-#[cfg(tests)]
-mod tests_day5 {
-    use super::*;
+/// Write `ship` and `steps` in the format `parse_crate_file` reads.
+pub fn write_crate_file(path: &str, ship: &ShipState, steps: &[Step]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "{}", ship)?;
+    write_steps(&mut writer, steps)?;
+    writer.flush()
 }
@@ -1,18 +1,22 @@
+use day5::crane::{Crane, CrateMover9000, CrateMover9001};
 use day5::*;
 
-fn part1() {
-    let (mut ship, steps) = parse_crate_file("day5/input.txt");
-    ship.execute(&steps);
-    println!("{}", ship.top_stacks());
-}
+use std::process;
 
-fn part2() {
-    let (mut ship, steps) = parse_crate_file("day5/input.txt");
-    ship.execute_pt2(&steps);
+fn run(crane: &dyn Crane) {
+    let path = "day5/input.txt";
+    let (mut ship, steps) = parse_crate_file_strict(path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    });
+    ship.execute(crane, &steps).unwrap_or_else(|e| {
+        eprintln!("{}: {}", crane.name(), e);
+        process::exit(1);
+    });
     println!("{}", ship.top_stacks());
 }
 
 pub fn main() {
-    part1();
-    part2();
+    run(&CrateMover9000);
+    run(&CrateMover9001);
 }
@@ -1,37 +1,43 @@
 #[cfg(test)]
 mod tests_day5 {
+    use day5::crane::*;
+    use day5::error::{ExecError, ExecErrorKind, ParseError};
+    use day5::journal::Journal;
     use day5::*;
 
+    use std::env;
+    use std::fs;
     use std::iter::zip;
 
     #[test]
     fn crate_parse() {
-        assert_eq!(Crate::parse("[B]").unwrap().id, 'B');
+        assert_eq!(Crate::parse("[B]").unwrap().id, "B");
+        assert_eq!(Crate::parse("[AB12]").unwrap().id, "AB12");
         assert!(Crate::parse("   ").is_none());
     }
 
     #[test]
     fn crate_parse_line() {
         fn do_compare(inp: &str, expected: &[Option<char>]) -> bool {
-            let parsed = ShipState::parse_crate_line(inp);
+            let parsed = ShipState::parse_crate_line(inp).unwrap();
 
             for (mine, test) in zip(&parsed, expected) {
                 assert_eq!(mine.is_some(), test.is_some());
                 if mine.is_some() {
-                    assert_eq!(mine.as_ref().unwrap().id, test.unwrap());
+                    assert_eq!(mine.as_ref().unwrap().id, test.unwrap().to_string());
                 }
             }
             true
         }
 
-        do_compare("    [D]    ", &vec![None, Some('D'), None]);
-        do_compare("[N] [C]    ", &vec![Some('N'), Some('C'), None]);
-        do_compare("[Z] [M] [P]", &vec![Some('Z'), Some('M'), Some('P')]);
+        do_compare("    [D]    ", &[None, Some('D'), None]);
+        do_compare("[N] [C]    ", &[Some('N'), Some('C'), None]);
+        do_compare("[Z] [M] [P]", &[Some('Z'), Some('M'), Some('P')]);
     }
 
     #[test]
     fn test_parse_ship() {
-        let ship = ShipState::from_path("input_test.txt");
+        let ship = ShipState::from_path("input_test.txt").unwrap();
 
         assert_eq!(ship.num_stacks(), 3);
         assert_eq!(ship.top_stacks(), "NDP");
@@ -39,26 +45,356 @@ mod tests_day5 {
 
     #[test]
     fn test_move_crates() {
-        let mut ship = ShipState::from_path("input_test.txt");
-        ship.move_crates(2, 2, 3);
+        let mut ship = ShipState::from_path("input_test.txt").unwrap();
+        ship.move_crates(2, 2, 3).unwrap();
         assert_eq!(ship.top_stacks(), "NMC");
+
+        ship.push(1, Crate::new("X")).unwrap();
+        assert_eq!(ship.top_stacks(), "XMC");
+        for stack in [0, 4] {
+            assert_eq!(
+                ship.push(stack, Crate::new("Y")),
+                Err(ExecErrorKind::InvalidStack { stack })
+            );
+        }
     }
 
     #[test]
     fn test_pt1() {
-        let (mut ship, steps) = parse_crate_file("input_test.txt");
+        let (mut ship, steps) = parse_crate_file("input_test.txt").unwrap();
 
-        ship.execute(&steps);
+        assert_eq!(ship.execute(&CrateMover9000, &steps).unwrap(), 7);
 
         assert_eq!(ship.top_stacks(), "CMZ");
     }
 
     #[test]
     fn test_pt2() {
-        let (mut ship, steps) = parse_crate_file("input_test.txt");
+        let (mut ship, steps) = parse_crate_file("input_test.txt").unwrap();
 
-        ship.execute_pt2(&steps);
+        assert_eq!(ship.execute(&CrateMover9001, &steps).unwrap(), 4);
 
         assert_eq!(ship.top_stacks(), "MCD");
     }
+
+    #[test]
+    fn test_crane_models() {
+        let (ship, steps) = parse_crate_file("input_test.txt").unwrap();
+        let run = |crane: &dyn Crane| {
+            let mut ship = ship.clone();
+            let ops = ship.execute(crane, &steps).unwrap();
+            (ops, ship.top_stacks())
+        };
+
+        assert_eq!(
+            run(&MaxLiftCrane::new(1).unwrap()),
+            (7, String::from("CMZ"))
+        );
+        assert_eq!(run(&MaxLiftCrane::new(2).unwrap()).0, 5);
+        assert!(MaxLiftCrane::new(0).is_none());
+        assert_eq!(run(&BottomFirstCrane).0, 7);
+    }
+
+    /// Lifts two crates at a time, then any odd one left over, relying on
+    /// the primitives to reject bad steps.
+    struct PairCrane;
+
+    impl Crane for PairCrane {
+        fn name(&self) -> String {
+            String::from("pairs")
+        }
+
+        fn apply(&self, ship: &mut ShipState, step: &Step) -> Result<usize, ExecErrorKind> {
+            let count = step.count as usize;
+            for _ in 0..count / 2 {
+                ship.lift(2, step.from, step.to)?;
+            }
+            if count % 2 == 1 {
+                ship.lift(1, step.from, step.to)?;
+            }
+            Ok(count.div_ceil(2))
+        }
+    }
+
+    #[test]
+    fn test_external_crane() {
+        let (mut ship, steps) = parse_crate_file("input_test.txt").unwrap();
+        assert_eq!(ship.execute(&PairCrane, &steps).unwrap(), 5);
+        assert_eq!(ship.top_stacks(), "MCZ");
+
+        let mut ship = ShipState::new(2);
+        assert_eq!(
+            PairCrane.apply(&mut ship, &Step::new(1, 0, 1)),
+            Err(ExecErrorKind::InvalidStack { stack: 0 })
+        );
+        assert_eq!(
+            ship.lift_bottom(1, 2),
+            Err(ExecErrorKind::NotEnoughCrates {
+                stack: 1,
+                needed: 1,
+                available: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut ship = ShipState::from_path("input_test.txt").unwrap();
+        let steps = vec![
+            Step::new(1, 0, 1),
+            Step::new(3, 1, 2),
+            Step::new(2, 1, 4),
+            Step::new(2, 3, 1),
+        ];
+
+        assert_eq!(
+            ship.validate(&steps),
+            vec![
+                ExecError {
+                    step: 0,
+                    kind: ExecErrorKind::InvalidStack { stack: 0 }
+                },
+                ExecError {
+                    step: 1,
+                    kind: ExecErrorKind::NotEnoughCrates {
+                        stack: 1,
+                        needed: 3,
+                        available: 2
+                    }
+                },
+                ExecError {
+                    step: 2,
+                    kind: ExecErrorKind::InvalidStack { stack: 4 }
+                },
+                ExecError {
+                    step: 3,
+                    kind: ExecErrorKind::NotEnoughCrates {
+                        stack: 3,
+                        needed: 2,
+                        available: 1
+                    }
+                },
+            ]
+        );
+
+        // Nothing moves when any step fails
+        let err = ship.execute(&CrateMover9000, &steps[1..]).unwrap_err();
+        assert_eq!(err.to_string(), "step 1: stack 1 holds 2 crates, 3 needed");
+        assert_eq!(ship.top_stacks(), "NDP");
+        assert!(ship.move_crates(1, 2, 0).is_err());
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let (_, steps) = parse_crate_file_strict("input_test.txt").unwrap();
+        assert_eq!(steps.len(), 4);
+        assert!(matches!(
+            parse_crate_file_strict("missing.txt"),
+            Err(ParseError::Io(_))
+        ));
+        assert!(matches!(
+            ShipState::from_file(&mut &b"[A]\n\xff\n"[..]),
+            Err(ParseError::Io(_))
+        ));
+
+        let drawing = "[A]\n 1 \n\nmove 1 from 1 to 1\nmove one from 1 to 1\n";
+        let mut reader = drawing.as_bytes();
+        let ship = ShipState::from_file(&mut reader).unwrap();
+        assert_eq!(ship.top_stacks(), "A");
+        match read_steps(&mut reader, ParseMode::Strict, 4) {
+            Err(ParseError::UnrecognisedLine { line, text }) => {
+                assert_eq!((line, text.as_str()), (5, "move one from 1 to 1"))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let mut reader = "move 1 from 1 to 1\nmove one from 1 to 1\n".as_bytes();
+        let steps = read_steps(&mut reader, ParseMode::Lenient, 1).unwrap();
+        assert_eq!(steps.len(), 1);
+    }
+
+    #[test]
+    fn test_no_footer() {
+        let path = env::temp_dir().join(format!("day5-no-footer-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        fs::write(path, "[A] [B]\n\nmove 1 from 1 to 2\nmove 1 from 2 to 1\n").unwrap();
+        let (ship, steps) = parse_crate_file_strict(path).unwrap();
+        assert_eq!(ship.top_stacks(), "AB");
+        assert_eq!(steps, vec![Step::new(1, 1, 2), Step::new(1, 2, 1)]);
+
+        fs::write(
+            path,
+            "[A] [B]\n\nmove 1 from 1 to 2\nmove one from 2 to 1\n",
+        )
+        .unwrap();
+        match parse_crate_file_strict(path) {
+            Err(ParseError::UnrecognisedLine { line, .. }) => assert_eq!(line, 4),
+            other => panic!("unexpected result {:?}", other),
+        }
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_footer_without_blank() {
+        let path = env::temp_dir().join(format!("day5-no-blank-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        fs::write(path, "[A]\n 1 \nmove 1 from 1 to 2\n").unwrap();
+        let (mut ship, steps) = parse_crate_file_strict(path).unwrap();
+        assert_eq!(steps, vec![Step::new(1, 1, 2)]);
+        assert_eq!(
+            ship.execute(&CrateMover9000, &steps),
+            Err(ExecError {
+                step: 0,
+                kind: ExecErrorKind::InvalidStack { stack: 2 }
+            })
+        );
+        assert_eq!(parse_crate_file(path).unwrap().1, steps);
+
+        fs::write(path, "[A]\n 1 \ngarbage\n").unwrap();
+        match parse_crate_file_strict(path) {
+            Err(ParseError::UnrecognisedLine { line, text }) => {
+                assert_eq!((line, text.as_str()), (3, "garbage"))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_misplaced_crates() {
+        let misplaced = |drawing: &str| {
+            matches!(
+                ShipState::from_file(&mut drawing.as_bytes()),
+                Err(ParseError::MisplacedCrate { .. })
+            )
+        };
+        assert!(misplaced("[A] [B]\n 1\n\n"));
+        assert!(misplaced("[A][B]\n   1\n\n"));
+        assert!(misplaced("[A]\n    1\n\n"));
+        assert!(!misplaced("    [B]\n[A] [C]\n 1   2\n\n"));
+
+        match ShipState::parse_crate_row("[A] [B]", &[1]) {
+            Err(ParseError::MisplacedCrate { label, text }) => {
+                assert_eq!((label.as_str(), text.as_str()), ("B", "[A] [B]"))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let path = env::temp_dir().join(format!("day5-misplaced-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, "[A] [B]\n 1\n\n").unwrap();
+        assert!(matches!(
+            parse_crate_file(path),
+            Err(ParseError::MisplacedCrate { .. })
+        ));
+        fs::remove_file(path).unwrap();
+        assert!(matches!(
+            parse_crate_file("missing.txt"),
+            Err(ParseError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_journal_seek() {
+        let (ship, steps) = parse_crate_file("input_test.txt").unwrap();
+        let mut journal = Journal::new(ship.clone());
+        assert_eq!(journal.execute(&CrateMover9000, &steps).unwrap(), 7);
+        assert_eq!(journal.ship().top_stacks(), "CMZ");
+
+        for position in [2, 0, 4, 1, 3] {
+            let mut replayed = ship.clone();
+            replayed
+                .execute(&CrateMover9000, &steps[..position])
+                .unwrap();
+            journal.seek(position);
+            assert_eq!(journal.ship(), &replayed);
+            assert_eq!(journal.snapshot_at(4).top_stacks(), "CMZ");
+        }
+
+        assert!(journal.execute(&CrateMover9000, &steps).is_err());
+        assert_eq!(journal.len(), 4);
+    }
+
+    #[test]
+    fn test_display() {
+        let input = fs::read_to_string("input_test.txt").unwrap();
+        let (ship, steps) = parse_crate_file("input_test.txt").unwrap();
+
+        let mut written = format!("{}\n", ship).into_bytes();
+        write_steps(&mut written, &steps).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), input);
+
+        assert_eq!(ShipState::new(2).to_string(), " 1   2 \n");
+    }
+
+    #[test]
+    fn test_mixed_widths() {
+        let drawing = "   [AB12]\n[C] [D]      [E9]\n 1     2      3\n\nmove 1 from 2 to 3\n";
+        let mut reader = drawing.as_bytes();
+        let mut ship = ShipState::from_file(&mut reader).unwrap();
+        let steps = read_steps(&mut reader, ParseMode::Strict, 4).unwrap();
+
+        assert_eq!(ship.num_stacks(), 3);
+        assert_eq!(ship.top_labels(), vec![Some("C"), Some("AB12"), Some("E9")]);
+        assert_eq!(ship.top_stacks(), "CAB12E9");
+
+        ship.execute(&CrateMover9000, &steps).unwrap();
+        assert_eq!(ship.top_labels(), vec![Some("C"), Some("D"), Some("AB12")]);
+        assert_eq!(
+            ship.to_string(),
+            "        [AB12]\n[C] [D]  [E9] \n 1   2    3   \n"
+        );
+
+        let emptied = ShipState::new(2);
+        assert_eq!(emptied.top_labels(), vec![None, None]);
+        assert_eq!(emptied.top_stacks(), "  ");
+    }
+
+    const LABEL_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    /// Random ships and valid procedures survive a trip through a file.
+    #[test]
+    fn test_write_round_trip() {
+        let mut state: u64 = 5;
+        let mut next = |n: u32| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) % n as u64) as u32
+        };
+        let path = env::temp_dir().join(format!("day5-round-trip-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        for _ in 0..200 {
+            let num_stacks = next(12);
+            let mut ship = ShipState::new(num_stacks);
+            let mut heights = vec![0; num_stacks as usize];
+            for _ in 0..next(40).min(num_stacks * 40) {
+                let stack = next(num_stacks) + 1;
+                let id: String = (0..next(4) + 1)
+                    .map(|_| LABEL_CHARS[next(36) as usize] as char)
+                    .collect();
+                ship.push(stack, Crate { id }).unwrap();
+                heights[stack as usize - 1] += 1;
+            }
+
+            let mut steps = vec![];
+            for _ in 0..next(20).min(num_stacks * 20) {
+                let (from, to) = (next(num_stacks) + 1, next(num_stacks) + 1);
+                let count = next(heights[from as usize - 1] + 1);
+                heights[from as usize - 1] -= count;
+                heights[to as usize - 1] += count;
+                steps.push(Step::new(count, from, to));
+            }
+
+            write_crate_file(path, &ship, &steps).unwrap();
+            let (parsed_ship, parsed_steps) = parse_crate_file(path).unwrap();
+            assert_eq!(parsed_ship, ship);
+            assert_eq!(parsed_steps, steps);
+            assert!(ship.validate(&steps).is_empty());
+        }
+        fs::remove_file(path).unwrap();
+    }
 }